@@ -0,0 +1,386 @@
+use std::error::Error;
+use std::io::Write;
+
+use serde_json::{json, Map, Value};
+
+use crate::session::Input;
+
+/// The kinds of question, in the order they are numbered when asked for.
+const KINDS: [(&str, &str); 4] = [
+    ("true_false", "true/false"),
+    ("selection", "selection"),
+    ("user_input", "free text"),
+    ("order", "order"),
+];
+
+/// Writes quizzes by asking for each part of their questions on `output`
+/// and reading the answers from `input`.
+pub struct Wizard<'a, I: Input, W: Write> {
+    input: &'a mut I,
+    output: &'a mut W,
+}
+
+impl<'a, I: Input, W: Write> Wizard<'a, I, W> {
+    pub fn new(input: &'a mut I, output: &'a mut W) -> Self {
+        Self { input, output }
+    }
+
+    /// Asks for `prompt` and returns the trimmed line typed.
+    async fn ask(&mut self, prompt: &str) -> std::io::Result<String> {
+        write!(self.output, "{prompt}: ")?;
+        self.output.flush()?;
+
+        match self.input.read_line().await? {
+            Some(line) => Ok(line.trim().to_string()),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "The input ran out before the quiz was written",
+            )),
+        }
+    }
+
+    /// Asks for `prompt` until something is typed.
+    async fn ask_required(&mut self, prompt: &str) -> std::io::Result<String> {
+        loop {
+            let line = self.ask(prompt).await?;
+            if !line.is_empty() {
+                return Ok(line);
+            }
+            writeln!(self.output, "This cannot be left empty")?;
+        }
+    }
+
+    /// Asks for `prompt`, `None` when it is left empty.
+    async fn ask_optional(&mut self, prompt: &str) -> std::io::Result<Option<String>> {
+        let line = self.ask(&format!("{prompt} (optional)")).await?;
+        Ok(Some(line).filter(|x| !x.is_empty()))
+    }
+
+    /// Asks a yes or no question, `default` is the answer when it is left
+    /// empty.
+    async fn confirm(&mut self, prompt: &str, default: bool) -> std::io::Result<bool> {
+        let choices = match default {
+            true => "Y/n",
+            false => "y/N",
+        };
+        loop {
+            let line = self.ask(&format!("{prompt} [{choices}]")).await?;
+            match line.to_lowercase().as_str() {
+                "" => return Ok(default),
+                "y" | "yes" => return Ok(true),
+                "n" | "no" => return Ok(false),
+                _ => writeln!(self.output, "Type y or n")?,
+            }
+        }
+    }
+
+    async fn ask_kind(&mut self) -> std::io::Result<&'static str> {
+        let choices: Vec<String> = KINDS
+            .iter()
+            .enumerate()
+            .map(|(number, (_, name))| format!("{} {name}", number + 1))
+            .collect();
+        let prompt = format!("Kind ({})", choices.join(", "));
+        loop {
+            let line = self.ask(&prompt).await?;
+            let kind = KINDS.iter().enumerate().find(|(number, (kind, name))| {
+                line == (number + 1).to_string()
+                    || line.eq_ignore_ascii_case(kind)
+                    || line.eq_ignore_ascii_case(name)
+            });
+            match kind {
+                Some((_, (kind, _))) => return Ok(kind),
+                None => writeln!(self.output, "Type a number from 1 to {}", KINDS.len())?,
+            }
+        }
+    }
+
+    /// Asks for answers, each with an optional explanation, until one is left
+    /// empty. There are at least `min` of them.
+    async fn ask_answers(&mut self, what: &str, min: usize) -> std::io::Result<Vec<Value>> {
+        writeln!(
+            self.output,
+            "{what}, leave it empty once they are all typed"
+        )?;
+
+        let mut answers = Vec::new();
+        loop {
+            let answer = self.ask(&format!("Answer {}", answers.len() + 1)).await?;
+            if answer.is_empty() {
+                if answers.len() >= min {
+                    return Ok(answers);
+                }
+                writeln!(self.output, "At least {min} answer is needed")?;
+                continue;
+            }
+
+            let mut object = Map::new();
+            object.insert("answer".to_string(), Value::String(answer));
+            if let Some(explanation) = self.ask_optional("Explanation").await? {
+                object.insert("explanation".to_string(), Value::String(explanation));
+            }
+            answers.push(Value::Object(object));
+        }
+    }
+
+    /// Asks for every part of a question of the kind picked first.
+    pub async fn ask_question(&mut self) -> std::io::Result<Value> {
+        let kind = self.ask_kind().await?;
+        let question = self.ask_required("Question").await?;
+
+        let answers = match kind {
+            "true_false" => {
+                let truth = self.confirm("Is it true?", true).await?;
+                let (correct, incorrect) = match truth {
+                    true => ("True", "False"),
+                    false => ("False", "True"),
+                };
+                let mut answers = Map::new();
+                for (field, answer) in [("correct", correct), ("incorrect", incorrect)] {
+                    let mut object = Map::new();
+                    object.insert("answer".to_string(), Value::String(answer.to_string()));
+                    let prompt = format!("Explanation for {answer}");
+                    if let Some(explanation) = self.ask_optional(&prompt).await? {
+                        object.insert("explanation".to_string(), Value::String(explanation));
+                    }
+                    answers.insert(field.to_string(), Value::Object(object));
+                }
+                Value::Object(answers)
+            }
+            "selection" => json!({
+                "correct": self.ask_answers("Correct answers", 1).await?,
+                "incorrect": self.ask_answers("Incorrect answers", 0).await?,
+            }),
+            "user_input" => {
+                writeln!(
+                    self.output,
+                    "Accepted answers, leave it empty once they are all typed"
+                )?;
+                let mut accepted = Vec::new();
+                loop {
+                    let answer = self.ask(&format!("Answer {}", accepted.len() + 1)).await?;
+                    match (answer.is_empty(), accepted.is_empty()) {
+                        (false, _) => accepted.push(answer),
+                        (true, false) => break,
+                        (true, true) => writeln!(self.output, "At least 1 answer is needed")?,
+                    }
+                }
+
+                let mut answers = json!({
+                    "accepted": accepted,
+                    "caseSensitive": self.confirm("Case sensitive?", false).await?,
+                });
+                if let Some(explanation) = self.ask_optional("Explanation").await? {
+                    answers["explanation"] = Value::String(explanation);
+                }
+                answers
+            }
+            _ => json!({"correct": self.ask_answers("Items in their correct order", 1).await?}),
+        };
+
+        Ok(json!({"kind": kind, "question": question, "answers": answers}))
+    }
+
+    /// Asks for questions to add to `quiz` until the user stops.
+    pub async fn add_questions(&mut self, quiz: &mut Value) -> Result<(), Box<dyn Error>> {
+        let Some(questions) = quiz.get_mut("questions").and_then(Value::as_array_mut) else {
+            return Err("The quiz has no list of questions to add to".into());
+        };
+
+        loop {
+            writeln!(self.output, "\nQuestion {}", questions.len() + 1)?;
+            questions.push(self.ask_question().await?);
+            if !self.confirm("Add another question?", true).await? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Asks for the title and description of a new quiz, then its questions.
+    pub async fn new_quiz(&mut self) -> Result<Value, Box<dyn Error>> {
+        let mut quiz = json!({"questions": []});
+        for (field, prompt) in [("title", "Title"), ("description", "Description")] {
+            if let Some(value) = self.ask_optional(prompt).await? {
+                quiz[field] = Value::String(value);
+            }
+        }
+
+        self.add_questions(&mut quiz).await?;
+        Ok(quiz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    async fn write_question(input: &str) -> std::io::Result<Value> {
+        let mut input = input.as_bytes();
+        let mut output = Vec::new();
+        Wizard::new(&mut input, &mut output).ask_question().await
+    }
+
+    #[tokio::test]
+    async fn test_ask_question() {
+        let cases = vec![
+            (
+                "true/false",
+                "1\nIs 9 afraid of 7?\n\nBecause 7 8 9\nIt is\n",
+                json!({
+                    "kind": "true_false",
+                    "question": "Is 9 afraid of 7?",
+                    "answers": {
+                        "correct": {"answer": "True", "explanation": "Because 7 8 9"},
+                        "incorrect": {"answer": "False", "explanation": "It is"}
+                    }
+                }),
+            ),
+            (
+                "false",
+                "true/false\nIs 7 afraid of 9?\nn\n\nIt is 9 that is afraid\n",
+                json!({
+                    "kind": "true_false",
+                    "question": "Is 7 afraid of 9?",
+                    "answers": {
+                        "correct": {"answer": "False"},
+                        "incorrect": {"answer": "True", "explanation": "It is 9 that is afraid"}
+                    }
+                }),
+            ),
+            (
+                "selection",
+                "2\nWhich are Straw Hats?\nZoro\n\nNami\nThe navigator\n\nBuggy\n\n\n",
+                json!({
+                    "kind": "selection",
+                    "question": "Which are Straw Hats?",
+                    "answers": {
+                        "correct": [{"answer": "Zoro"}, {"answer": "Nami", "explanation": "The navigator"}],
+                        "incorrect": [{"answer": "Buggy"}]
+                    }
+                }),
+            ),
+            (
+                "empty answers asked again",
+                "selection\n\nWhich are Straw Hats?\n\nZoro\n\n\n\n",
+                json!({
+                    "kind": "selection",
+                    "question": "Which are Straw Hats?",
+                    "answers": {"correct": [{"answer": "Zoro"}], "incorrect": []}
+                }),
+            ),
+            (
+                "free text",
+                "3\nWhat is Luffy called in Dressrosa?\nLucy\n Lucy the gladiator \n\nyes\nA disguise\n",
+                json!({
+                    "kind": "user_input",
+                    "question": "What is Luffy called in Dressrosa?",
+                    "answers": {
+                        "accepted": ["Lucy", "Lucy the gladiator"],
+                        "caseSensitive": true,
+                        "explanation": "A disguise"
+                    }
+                }),
+            ),
+            (
+                "invalid kind asked again",
+                "5\nquiz\nuser_input\nWho?\n\nLuffy\n\n\n\n",
+                json!({
+                    "kind": "user_input",
+                    "question": "Who?",
+                    "answers": {"accepted": ["Luffy"], "caseSensitive": false}
+                }),
+            ),
+            (
+                "order",
+                "4\nOrder these arcs\nAmazon Lily\n\nImpel Down\nThe prison\n\n",
+                json!({
+                    "kind": "order",
+                    "question": "Order these arcs",
+                    "answers": {
+                        "correct": [{"answer": "Amazon Lily"}, {"answer": "Impel Down", "explanation": "The prison"}]
+                    }
+                }),
+            ),
+        ];
+
+        let compiled = schema::compile_json_schema(&schema::bundled_schema()).unwrap();
+        for (case_name, input, expected) in cases {
+            let question = write_question(input).await.unwrap();
+            assert_eq!(question, expected, "case: {case_name} failed");
+
+            let quiz = json!({"questions": [question]});
+            assert!(
+                schema::validate_json(&compiled, &quiz).is_ok(),
+                "case: {case_name} failed"
+            );
+        }
+
+        let error = write_question("2\nWhich?\n").await.unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[tokio::test]
+    async fn test_new_quiz() {
+        let mut input =
+            "One Piece\n\n1\nIs 9 afraid of 7?\n\n\n\ny\n3\nWho?\nLuffy\n\n\n\nn\n".as_bytes();
+        let mut output = Vec::new();
+        let quiz = Wizard::new(&mut input, &mut output)
+            .new_quiz()
+            .await
+            .unwrap();
+
+        assert_eq!(quiz["title"], "One Piece");
+        assert_eq!(quiz.get("description"), None);
+        let questions: Vec<&Value> = quiz["questions"].as_array().unwrap().iter().collect();
+        assert_eq!(questions.len(), 2);
+        assert_eq!(questions[0]["kind"], "true_false");
+        assert_eq!(questions[1]["question"], "Who?");
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Question 2"), "{output}");
+    }
+
+    #[tokio::test]
+    async fn test_add_questions() {
+        let cases = vec![
+            (
+                "appended",
+                json!({"title": "One Piece", "questions": [{"kind": "order"}]}),
+                Ok(2),
+            ),
+            (
+                "no questions",
+                json!({"title": "One Piece"}),
+                Err("The quiz has no list of questions to add to".to_string()),
+            ),
+        ];
+
+        for (case_name, mut quiz, expected) in cases {
+            let mut input = "3\nWho?\nLuffy\n\n\n\nno\n".as_bytes();
+            let mut output = Vec::new();
+            let result = Wizard::new(&mut input, &mut output)
+                .add_questions(&mut quiz)
+                .await
+                .map(|_| quiz["questions"].as_array().unwrap().len())
+                .map_err(|e| e.to_string());
+            assert_eq!(result, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_questions_keeps_key_order() {
+        let text = r#"{"title":"One Piece","questions":[{"question":"Who?","kind":"user_input","answers":{"accepted":["Luffy"]}}]}"#;
+        let mut quiz: Value = serde_json::from_str(text).unwrap();
+        let mut input = "3\nWho?\nZoro\n\n\n\nno\n".as_bytes();
+        let mut output = Vec::new();
+        Wizard::new(&mut input, &mut output)
+            .add_questions(&mut quiz)
+            .await
+            .unwrap();
+
+        let written = serde_json::to_string(&quiz).unwrap();
+        assert!(written.starts_with(&text[..text.len() - 2]), "{written}");
+        assert!(written.contains(r#"{"kind":"user_input","question":"Who?","answers""#));
+    }
+}
@@ -0,0 +1,659 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::lint;
+use crate::quizlit::Scoring;
+use crate::schema::V1_SCHEMA_ID;
+use crate::session::Mode;
+use crate::source::Source;
+
+/// Something went wrong while loading or running the quiz.
+pub const EXIT_RUNTIME_ERROR: u8 = 1;
+/// The quiz does not match the schema.
+pub const EXIT_VALIDATION_ERROR: u8 = 3;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "Take quizlit quizzes in the terminal")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Take a quiz
+    Run(RunArgs),
+    /// List the unfinished sessions, or go on with one of them
+    Resume(ResumeArgs),
+    /// Show how many questions are due for study per quiz
+    Due,
+    /// Show how the attempts at each quiz went
+    Stats(StatsArgs),
+    /// Write a new quiz file, asking for each of its questions
+    New(NewArgs),
+    /// Add questions to a quiz file, asking for each part of them
+    AddQuestion(AddQuestionArgs),
+    /// Check quiz files against the schema without taking them
+    Validate(ValidateArgs),
+}
+
+/// How a quiz is shown.
+#[derive(Debug, PartialEq, Clone, Copy, Default, clap::ValueEnum)]
+pub enum Interface {
+    /// Full screen when running in a terminal, line by line otherwise
+    #[default]
+    Auto,
+    /// Full screen with keyboard navigation
+    Tui,
+    /// Line by line, for dumb terminals and piping
+    Line,
+}
+
+/// Which schema quizzes are validated against.
+#[derive(Args, Debug)]
+pub struct SchemaArgs {
+    /// Schema to validate the quiz against instead of the bundled v1 schema:
+    /// a file path or an http(s) URL. URLs and their references are downloaded.
+    #[arg(long, value_parser = Source::parse)]
+    pub schema: Option<Source>,
+
+    /// Download the latest v1 schema instead of using the bundled copy
+    #[arg(long, conflicts_with = "schema")]
+    pub latest_schema: bool,
+}
+
+impl SchemaArgs {
+    /// The schema to fetch, `None` means the bundled schema is used.
+    pub fn source(&self) -> Option<Source> {
+        match self.latest_schema {
+            true => Some(Source::Url(V1_SCHEMA_ID.to_string())),
+            false => self.schema.clone(),
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RunArgs {
+    /// Quiz to take: a file path, an http(s) URL or "-" to read it from stdin
+    #[arg(value_parser = Source::parse)]
+    pub quiz: Source,
+
+    #[command(flatten)]
+    pub schema: SchemaArgs,
+
+    /// Skip validating the quiz against the schema
+    #[arg(long)]
+    pub no_validate: bool,
+
+    /// Seed for shuffling, pass the seed printed with the results to get the same layout again
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Show the options of selection questions in the order of the quiz file
+    #[arg(long)]
+    pub no_shuffle: bool,
+
+    /// Ask the questions in a random order
+    #[arg(long)]
+    pub shuffle_questions: bool,
+
+    /// How selection questions with several correct answers are scored
+    #[arg(long, value_enum, default_value_t = Scoring::default())]
+    pub scoring: Scoring,
+
+    /// Typos forgiven in free text answers, counted in edits, questions may set their own
+    #[arg(long, default_value_t = 0)]
+    pub typo_tolerance: usize,
+
+    /// Show whether each answer is correct, and why, right after answering it
+    #[arg(long)]
+    pub practice: bool,
+
+    /// Go through the questions as flashcards: show the answer on a key press
+    /// and grade how well it was remembered
+    #[arg(long, conflicts_with = "practice")]
+    pub flashcards: bool,
+
+    /// Show free text flashcards from their answer, to recall the question
+    #[arg(long, requires = "flashcards")]
+    pub reversed: bool,
+
+    /// How the quiz is shown
+    #[arg(long, value_enum, default_value_t = Interface::default())]
+    pub interface: Interface,
+
+    /// Do not save the session, it cannot be resumed if it is interrupted
+    #[arg(long)]
+    pub no_save: bool,
+
+    /// Only ask the questions that are due for review, and schedule when to
+    /// ask them again depending on the answers
+    #[arg(long)]
+    pub study: bool,
+
+    /// Only ask the questions missed in this attempt at the quiz, as numbered
+    /// by the stats command
+    #[arg(long, value_name = "ATTEMPT", conflicts_with = "study", value_parser = clap::value_parser!(u64).range(1..))]
+    pub only_missed: Option<u64>,
+
+    /// Time for the whole quiz, e.g. 90s, 20m or 1h30m, the answers are
+    /// submitted when it runs out
+    #[arg(long, value_parser = parse_duration)]
+    pub time_limit: Option<Duration>,
+
+    /// Time for each question, the quiz moves on to the next question when it runs out
+    #[arg(long, value_parser = parse_duration)]
+    pub question_time_limit: Option<Duration>,
+}
+
+#[derive(Args, Debug)]
+pub struct ResumeArgs {
+    /// Session to resume, or the start of its id, as listed without it
+    pub id: Option<String>,
+
+    /// How the quiz is shown
+    #[arg(long, value_enum, default_value_t = Interface::default())]
+    pub interface: Interface,
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Only show the attempts at this quiz: a file path or an http(s) URL
+    #[arg(value_parser = Source::parse)]
+    pub quiz: Option<Source>,
+}
+
+#[derive(Args, Debug)]
+pub struct NewArgs {
+    /// Where to write the quiz
+    pub path: PathBuf,
+
+    /// Overwrite the file when it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AddQuestionArgs {
+    /// Quiz file to add the questions to
+    pub path: PathBuf,
+}
+
+/// How the validate command reports the problems it finds.
+#[derive(Debug, PartialEq, Clone, Copy, Default, clap::ValueEnum)]
+pub enum Format {
+    /// Compiler-style diagnostics, with the lines they are about
+    #[default]
+    Text,
+    /// A JSON report of every file, for CI
+    Json,
+}
+
+#[derive(Args, Debug)]
+pub struct ValidateArgs {
+    /// Quiz files, or patterns such as "quizzes/**/*.json" for the shells that
+    /// do not expand them
+    #[arg(required = true)]
+    pub files: Vec<String>,
+
+    #[command(flatten)]
+    pub schema: SchemaArgs,
+
+    /// How the problems found are reported
+    #[arg(long, value_enum, default_value_t = Format::default())]
+    pub format: Format,
+
+    /// Turn off a lint rule, can be repeated
+    #[arg(long, value_name = "RULE", value_parser = lint_rule())]
+    pub allow: Vec<String>,
+
+    /// Count lint warnings as errors, so that they fail the validation too
+    #[arg(long)]
+    pub deny_warnings: bool,
+}
+
+/// Accepts the ids of the lint rules, described in the help.
+fn lint_rule() -> clap::builder::PossibleValuesParser {
+    clap::builder::PossibleValuesParser::new(
+        lint::RULES
+            .iter()
+            .map(|x| clap::builder::PossibleValue::new(x.id).help(x.description)),
+    )
+}
+
+/// Parses a duration like "90", "90s", "20m" or "1h30m", plain numbers are seconds.
+pub fn parse_duration(data: &str) -> Result<Duration, String> {
+    let invalid = || format!("\"{data}\" is not a duration, e.g. 90s, 20m or 1h30m");
+
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in data.trim().chars() {
+        let unit = match c {
+            '0'..='9' => {
+                number.push(c);
+                continue;
+            }
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let count: u64 = number.parse().map_err(|_| invalid())?;
+        seconds = count
+            .checked_mul(unit)
+            .and_then(|x| seconds.checked_add(x))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        let count: u64 = number.parse().map_err(|_| invalid())?;
+        seconds = seconds.checked_add(count).ok_or_else(invalid)?;
+    }
+
+    match seconds {
+        0 => Err(format!(
+            "\"{data}\" is too short, a time limit has to be at least 1s"
+        )),
+        _ => Ok(Duration::from_secs(seconds)),
+    }
+}
+
+impl RunArgs {
+    pub fn mode(&self) -> Mode {
+        match (self.flashcards, self.reversed) {
+            (false, _) => Mode::Quiz,
+            (true, false) => Mode::Flashcards,
+            (true, true) => Mode::ReversedFlashcards,
+        }
+    }
+
+    /// The schema to fetch, `None` means the bundled schema is used.
+    pub fn schema_source(&self) -> Option<Source> {
+        self.schema.source()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_args(args: Vec<&str>) -> RunArgs {
+        match Cli::try_parse_from(args).unwrap().command {
+            Commands::Run(run) => run,
+            command => panic!("expected the run command, got {command:?}"),
+        }
+    }
+
+    #[test]
+    fn test_cli_run() {
+        let cases = vec![
+            (
+                "path",
+                vec!["quizlit", "run", "quiz.json"],
+                Source::Path(PathBuf::from("quiz.json")),
+                None,
+                false,
+            ),
+            (
+                "stdin with schema file",
+                vec!["quizlit", "run", "-", "--schema", "schema.json"],
+                Source::Stdin,
+                Some(Source::Path(PathBuf::from("schema.json"))),
+                false,
+            ),
+            (
+                "url without validation",
+                vec![
+                    "quizlit",
+                    "run",
+                    "https://example.com/q.json",
+                    "--no-validate",
+                ],
+                Source::Url("https://example.com/q.json".to_string()),
+                None,
+                true,
+            ),
+        ];
+
+        for (case_name, args, quiz, schema, no_validate) in cases {
+            let run = run_args(args);
+
+            assert_eq!(run.quiz, quiz, "case: {case_name} failed");
+            assert_eq!(run.schema_source(), schema, "case: {case_name} failed");
+            assert_eq!(run.no_validate, no_validate, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_cli_latest_schema() {
+        let run = run_args(vec!["quizlit", "run", "q.json", "--latest-schema"]);
+        assert_eq!(
+            run.schema_source(),
+            Some(Source::Url(V1_SCHEMA_ID.to_string()))
+        );
+
+        let conflicting = vec![
+            "quizlit",
+            "run",
+            "q.json",
+            "--latest-schema",
+            "--schema",
+            "s.json",
+        ];
+        assert!(Cli::try_parse_from(conflicting).is_err());
+    }
+
+    #[test]
+    fn test_cli_shuffle() {
+        let run = run_args(vec!["quizlit", "run", "q.json"]);
+        assert_eq!(run.seed, None);
+        assert!(!run.no_shuffle);
+        assert!(!run.shuffle_questions);
+        assert!(!run.practice);
+
+        let args = vec![
+            "quizlit",
+            "run",
+            "q.json",
+            "--seed",
+            "42",
+            "--no-shuffle",
+            "--shuffle-questions",
+            "--practice",
+        ];
+        let run = run_args(args);
+        assert_eq!(run.seed, Some(42));
+        assert!(run.no_shuffle);
+        assert!(run.shuffle_questions);
+        assert!(run.practice);
+    }
+
+    #[test]
+    fn test_cli_scoring() {
+        let cases = vec![
+            (vec![], Scoring::Proportional),
+            (vec!["--scoring", "all-or-nothing"], Scoring::AllOrNothing),
+            (vec!["--scoring", "penalty"], Scoring::Penalty),
+        ];
+
+        for (extra, expected) in cases {
+            let mut args = vec!["quizlit", "run", "q.json"];
+            args.extend(extra);
+            let run = run_args(args);
+            assert_eq!(run.scoring, expected);
+        }
+
+        let args = vec!["quizlit", "run", "q.json", "--scoring", "most"];
+        assert!(Cli::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn test_cli_typo_tolerance() {
+        let run = run_args(vec!["quizlit", "run", "q.json"]);
+        assert_eq!(run.typo_tolerance, 0);
+
+        let args = vec!["quizlit", "run", "q.json", "--typo-tolerance", "2"];
+        let run = run_args(args);
+        assert_eq!(run.typo_tolerance, 2);
+    }
+
+    #[test]
+    fn test_cli_interface() {
+        let cases = vec![
+            (vec![], Interface::Auto),
+            (vec!["--interface", "tui"], Interface::Tui),
+            (vec!["--interface", "line"], Interface::Line),
+        ];
+
+        for (extra, expected) in cases {
+            let mut args = vec!["quizlit", "run", "q.json"];
+            args.extend(extra);
+            let run = run_args(args);
+            assert_eq!(run.interface, expected);
+        }
+    }
+
+    #[test]
+    fn test_cli_resume() {
+        let cases = vec![
+            ("list", vec!["quizlit", "resume"], None, Interface::Auto),
+            (
+                "id",
+                vec!["quizlit", "resume", "3fa2", "--interface", "line"],
+                Some("3fa2".to_string()),
+                Interface::Line,
+            ),
+        ];
+
+        for (case_name, args, id, interface) in cases {
+            let Commands::Resume(resume) = Cli::try_parse_from(args).unwrap().command else {
+                panic!("case: {case_name} failed");
+            };
+            assert_eq!(resume.id, id, "case: {case_name} failed");
+            assert_eq!(resume.interface, interface, "case: {case_name} failed");
+        }
+
+        assert!(!run_args(vec!["quizlit", "run", "q.json"]).no_save);
+        assert!(run_args(vec!["quizlit", "run", "q.json", "--no-save"]).no_save);
+    }
+
+    #[test]
+    fn test_cli_study() {
+        assert!(!run_args(vec!["quizlit", "run", "q.json"]).study);
+        assert!(run_args(vec!["quizlit", "run", "q.json", "--study"]).study);
+
+        let cli = Cli::try_parse_from(vec!["quizlit", "due"]).unwrap();
+        assert!(matches!(cli.command, Commands::Due));
+    }
+
+    #[test]
+    fn test_cli_only_missed() {
+        assert_eq!(run_args(vec!["quizlit", "run", "q.json"]).only_missed, None);
+
+        let args = vec!["quizlit", "run", "q.json", "--only-missed", "3"];
+        assert_eq!(run_args(args).only_missed, Some(3));
+
+        for args in [
+            vec!["quizlit", "run", "q.json", "--only-missed", "0"],
+            vec!["quizlit", "run", "q.json", "--only-missed"],
+            vec!["quizlit", "run", "q.json", "--only-missed", "1", "--study"],
+        ] {
+            assert!(Cli::try_parse_from(args.clone()).is_err(), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn test_cli_flashcards() {
+        let cases = vec![
+            ("quiz", vec![], Some(Mode::Quiz)),
+            ("flashcards", vec!["--flashcards"], Some(Mode::Flashcards)),
+            (
+                "reversed",
+                vec!["--flashcards", "--reversed"],
+                Some(Mode::ReversedFlashcards),
+            ),
+            ("reversed without flashcards", vec!["--reversed"], None),
+            ("practice", vec!["--flashcards", "--practice"], None),
+        ];
+
+        for (case_name, extra, expected) in cases {
+            let mut args = vec!["quizlit", "run", "q.json"];
+            args.extend(extra);
+            let mode = Cli::try_parse_from(args).ok().map(|cli| match cli.command {
+                Commands::Run(run) => run.mode(),
+                command => panic!("expected the run command, got {command:?}"),
+            });
+            assert_eq!(mode, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_cli_stats() {
+        let cases = vec![
+            ("every quiz", vec!["quizlit", "stats"], None),
+            (
+                "one quiz",
+                vec!["quizlit", "stats", "quiz.json"],
+                Some(Source::Path(PathBuf::from("quiz.json"))),
+            ),
+        ];
+
+        for (case_name, args, quiz) in cases {
+            let Commands::Stats(stats) = Cli::try_parse_from(args).unwrap().command else {
+                panic!("case: {case_name} failed");
+            };
+            assert_eq!(stats.quiz, quiz, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_cli_authoring() {
+        let cases = vec![
+            (
+                "new",
+                vec!["quizlit", "new", "quiz.json"],
+                Some(("quiz.json", false)),
+            ),
+            (
+                "new forced",
+                vec!["quizlit", "new", "quiz.json", "--force"],
+                Some(("quiz.json", true)),
+            ),
+            (
+                "add question",
+                vec!["quizlit", "add-question", "quiz.json"],
+                Some(("quiz.json", false)),
+            ),
+            ("new without a path", vec!["quizlit", "new"], None),
+            (
+                "add question without a path",
+                vec!["quizlit", "add-question"],
+                None,
+            ),
+        ];
+
+        for (case_name, args, expected) in cases {
+            let parsed = Cli::try_parse_from(args).ok().map(|cli| match cli.command {
+                Commands::New(new) => (new.path, new.force),
+                Commands::AddQuestion(add) => (add.path, false),
+                command => panic!("expected an authoring command, got {command:?}"),
+            });
+            let expected = expected.map(|(path, force)| (PathBuf::from(path), force));
+            assert_eq!(parsed, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_cli_validate() {
+        let cases = vec![
+            (
+                "files",
+                vec!["quizlit", "validate", "a.json", "quizzes/*.json"],
+                Some((vec!["a.json", "quizzes/*.json"], None, Format::Text)),
+            ),
+            (
+                "json with a schema",
+                vec![
+                    "quizlit", "validate", "a.json", "--format", "json", "--schema", "s.json",
+                ],
+                Some((
+                    vec!["a.json"],
+                    Some(Source::Path(PathBuf::from("s.json"))),
+                    Format::Json,
+                )),
+            ),
+            (
+                "latest schema",
+                vec!["quizlit", "validate", "a.json", "--latest-schema"],
+                Some((
+                    vec!["a.json"],
+                    Some(Source::Url(V1_SCHEMA_ID.to_string())),
+                    Format::Text,
+                )),
+            ),
+            ("no files", vec!["quizlit", "validate"], None),
+            (
+                "unknown rule",
+                vec!["quizlit", "validate", "a.json", "--allow", "no-such-rule"],
+                None,
+            ),
+            (
+                "unknown format",
+                vec!["quizlit", "validate", "a.json", "--format", "xml"],
+                None,
+            ),
+        ];
+
+        for (case_name, args, expected) in cases {
+            let parsed = Cli::try_parse_from(args).ok().map(|cli| match cli.command {
+                Commands::Validate(validate) => {
+                    (validate.files, validate.schema.source(), validate.format)
+                }
+                command => panic!("expected the validate command, got {command:?}"),
+            });
+            let expected = expected.map(|(files, schema, format)| {
+                (
+                    files.into_iter().map(String::from).collect(),
+                    schema,
+                    format,
+                )
+            });
+            assert_eq!(parsed, expected, "case: {case_name} failed");
+        }
+
+        let args = vec![
+            "quizlit",
+            "validate",
+            "a.json",
+            "--allow",
+            "duplicate-question",
+            "--allow",
+            "single-item-order",
+            "--deny-warnings",
+        ];
+        let Commands::Validate(validate) = Cli::try_parse_from(args).unwrap().command else {
+            panic!("expected the validate command");
+        };
+        assert_eq!(
+            validate.allow,
+            vec!["duplicate-question", "single-item-order"]
+        );
+        assert!(validate.deny_warnings);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let cases = vec![
+            ("90", Ok(90)),
+            ("90s", Ok(90)),
+            ("20m", Ok(1200)),
+            ("1h30m", Ok(5400)),
+            ("1m30s", Ok(90)),
+            (" 2m ", Ok(120)),
+            ("", Err(())),
+            ("0s", Err(())),
+            ("m", Err(())),
+            ("1.5m", Err(())),
+            ("10 minutes", Err(())),
+            ("9999999999999999h", Err(())),
+            ("18446744073709551615s1s", Err(())),
+            ("99999999999999999999", Err(())),
+        ];
+
+        for (data, expected) in cases {
+            let result = parse_duration(data).map(|x| x.as_secs()).map_err(|_| ());
+            assert_eq!(result, expected, "case: '{data}' failed");
+        }
+
+        let run = run_args(vec!["quizlit", "run", "q.json", "--time-limit", "20m"]);
+        assert_eq!(run.time_limit, Some(Duration::from_secs(1200)));
+        assert_eq!(run.question_time_limit, None);
+    }
+
+    #[test]
+    fn test_cli_requires_quiz() {
+        assert!(Cli::try_parse_from(vec!["quizlit", "run"]).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+/// Lines starting with this are commands, anything else is an answer.
+pub const PREFIX: char = ':';
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    Next,
+    Previous,
+    /// Go to a question by its 1-based number.
+    Goto(usize),
+    Skip,
+    Flag,
+    List,
+    /// Go through only the questions passing the filter.
+    Only(Filter),
+    Hint,
+    Help,
+    Review,
+    /// Lock in the answers and grade them.
+    Submit,
+    Quit,
+    /// Stop without submitting, keeping the session to resume it later.
+    Save,
+    Answer(String),
+}
+
+/// Which questions `:only` goes through.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Filter {
+    Unanswered,
+    Flagged,
+    All,
+}
+
+impl Filter {
+    fn parse(argument: &str) -> Option<Self> {
+        match argument.to_lowercase().as_str() {
+            "unanswered" => Some(Self::Unanswered),
+            "flagged" => Some(Self::Flagged),
+            "all" => Some(Self::All),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unanswered => "unanswered",
+            Self::Flagged => "flagged",
+            Self::All => "all",
+        }
+    }
+}
+
+/// What follows the name of a command.
+pub enum Arguments {
+    None(Command),
+    /// A single argument, shown as the placeholder in help and parsed by the function.
+    One(&'static str, fn(&str) -> Option<Command>),
+}
+
+/// A command users can type. Parsing and help both come from `COMMANDS`.
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub arguments: Arguments,
+    pub help: &'static str,
+}
+
+impl CommandSpec {
+    /// How the command is typed, e.g. ":goto <number>".
+    pub fn usage(&self) -> String {
+        match self.arguments {
+            Arguments::One(placeholder, _) => format!("{PREFIX}{} {placeholder}", self.name),
+            Arguments::None(_) => format!("{PREFIX}{}", self.name),
+        }
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+            || self.aliases.iter().any(|x| x.eq_ignore_ascii_case(name))
+    }
+
+    fn parse(&self, argument: Option<&str>) -> Result<Command, String> {
+        match (&self.arguments, argument) {
+            (Arguments::None(command), None) => Ok(command.clone()),
+            (Arguments::None(_), Some(_)) => {
+                Err(format!("{PREFIX}{} does not take an argument", self.name))
+            }
+            (Arguments::One(_, parse), Some(argument)) => {
+                parse(argument).ok_or_else(|| format!("Usage: {}", self.usage()))
+            }
+            (Arguments::One(..), None) => Err(format!("Usage: {}", self.usage())),
+        }
+    }
+}
+
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "next",
+        aliases: &["n"],
+        arguments: Arguments::None(Command::Next),
+        help: "Go to the next question",
+    },
+    CommandSpec {
+        name: "previous",
+        aliases: &["p", "prev"],
+        arguments: Arguments::None(Command::Previous),
+        help: "Go to the previous question",
+    },
+    CommandSpec {
+        name: "goto",
+        aliases: &["g"],
+        arguments: Arguments::One("<number>", |x| {
+            x.parse().ok().filter(|x| *x > 0).map(Command::Goto)
+        }),
+        help: "Go to the question with this number",
+    },
+    CommandSpec {
+        name: "skip",
+        aliases: &["s"],
+        arguments: Arguments::None(Command::Skip),
+        help: "Go to the next unanswered question",
+    },
+    CommandSpec {
+        name: "flag",
+        aliases: &["f"],
+        arguments: Arguments::None(Command::Flag),
+        help: "Flag the question to come back to it later, or unflag it",
+    },
+    CommandSpec {
+        name: "list",
+        aliases: &["l", "ls"],
+        arguments: Arguments::None(Command::List),
+        help: "List the questions and which are answered or flagged",
+    },
+    CommandSpec {
+        name: "only",
+        aliases: &["o"],
+        arguments: Arguments::One("<filter>", |x| Filter::parse(x).map(Command::Only)),
+        help: "Go through only the unanswered or flagged questions, or all of them",
+    },
+    CommandSpec {
+        name: "hint",
+        aliases: &[],
+        arguments: Arguments::None(Command::Hint),
+        help: "Give a hint for the question",
+    },
+    CommandSpec {
+        name: "help",
+        aliases: &["h", "?"],
+        arguments: Arguments::None(Command::Help),
+        help: "Show this help",
+    },
+    CommandSpec {
+        name: "review",
+        aliases: &["r"],
+        arguments: Arguments::None(Command::Review),
+        help: "Review every answer before submitting",
+    },
+    CommandSpec {
+        name: "submit",
+        aliases: &[],
+        arguments: Arguments::None(Command::Submit),
+        help: "Lock in the answers and show the results",
+    },
+    CommandSpec {
+        name: "quit",
+        aliases: &["q", "exit"],
+        arguments: Arguments::None(Command::Quit),
+        help: "Stop and show the results",
+    },
+    CommandSpec {
+        name: "save",
+        aliases: &["w"],
+        arguments: Arguments::None(Command::Save),
+        help: "Save the session and stop, to resume it later",
+    },
+];
+
+/// Parses a line of input, a command like ":goto 3" or an answer like "a c".
+pub fn parse(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    if line.is_empty() {
+        return Err(format!("Type an answer, or {PREFIX}help for the commands"));
+    }
+
+    let Some(command) = line.strip_prefix(PREFIX) else {
+        return Ok(Command::Answer(line.to_string()));
+    };
+
+    let (name, argument) = match command.trim().split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, Some(argument.trim())),
+        None => (command.trim(), None),
+    };
+    match COMMANDS.iter().find(|x| x.matches(name)) {
+        Some(spec) => spec.parse(argument),
+        None => Err(format!(
+            "Unknown command \"{PREFIX}{name}\", {PREFIX}help lists the commands"
+        )),
+    }
+}
+
+/// The one line summary of the commands shown below every question.
+pub fn summary() -> String {
+    let commands: Vec<String> = COMMANDS.iter().map(CommandSpec::usage).collect();
+    format!("Commands: {}, <enter answer>", commands.join(", "))
+}
+
+/// Every command with its aliases and what it does.
+pub fn help() -> String {
+    let lines: Vec<String> = COMMANDS
+        .iter()
+        .map(|x| {
+            let mut usage = x.usage();
+            if !x.aliases.is_empty() {
+                let aliases: Vec<String> =
+                    x.aliases.iter().map(|a| format!("{PREFIX}{a}")).collect();
+                usage = format!("{usage} ({})", aliases.join(", "));
+            }
+            format!("{usage:<28}{}", x.help)
+        })
+        .collect();
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let cases = vec![
+            ("answer", "a c", Ok(Command::Answer("a c".to_string()))),
+            (
+                "trimmed answer",
+                "  Lucy ",
+                Ok(Command::Answer("Lucy".to_string())),
+            ),
+            ("command", ":next", Ok(Command::Next)),
+            ("alias", ":prev", Ok(Command::Previous)),
+            ("case insensitive", ":QUIT", Ok(Command::Quit)),
+            ("space after the prefix", ": flag", Ok(Command::Flag)),
+            ("argument", ":goto 12", Ok(Command::Goto(12))),
+            ("alias with argument", ":g  3 ", Ok(Command::Goto(3))),
+            ("review", ":r", Ok(Command::Review)),
+            ("submit", ":submit", Ok(Command::Submit)),
+            ("save", ":save", Ok(Command::Save)),
+            ("save alias", ":w", Ok(Command::Save)),
+            (
+                "filter",
+                ":only Flagged",
+                Ok(Command::Only(Filter::Flagged)),
+            ),
+            (
+                "filter alias",
+                ":o unanswered",
+                Ok(Command::Only(Filter::Unanswered)),
+            ),
+            (
+                "unknown filter",
+                ":only answered",
+                Err("Usage: :only <filter>".to_string()),
+            ),
+            ("bare letter", "n", Ok(Command::Answer("n".to_string()))),
+            ("bare word", "Quit", Ok(Command::Answer("Quit".to_string()))),
+            (
+                "bare word lowercase",
+                "next",
+                Ok(Command::Answer("next".to_string())),
+            ),
+            (
+                "empty line",
+                "  ",
+                Err("Type an answer, or :help for the commands".to_string()),
+            ),
+            (
+                "unknown command",
+                ":jump 3",
+                Err("Unknown command \":jump\", :help lists the commands".to_string()),
+            ),
+            (
+                "missing argument",
+                ":goto",
+                Err("Usage: :goto <number>".to_string()),
+            ),
+            (
+                "invalid argument",
+                ":goto zero",
+                Err("Usage: :goto <number>".to_string()),
+            ),
+            ("zero", ":goto 0", Err("Usage: :goto <number>".to_string())),
+            (
+                "unexpected argument",
+                ":skip 2",
+                Err(":skip does not take an argument".to_string()),
+            ),
+        ];
+
+        for (case_name, line, expected) in cases {
+            assert_eq!(parse(line), expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_every_name_is_unique() {
+        let mut names: Vec<&str> = COMMANDS
+            .iter()
+            .flat_map(|x| std::iter::once(x.name).chain(x.aliases.iter().copied()))
+            .collect();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn test_help() {
+        assert!(summary().starts_with("Commands: :next, :previous, :goto <number>, :skip,"));
+        assert!(summary().ends_with(":quit, :save, <enter answer>"));
+
+        let help = help();
+        assert_eq!(help.lines().count(), COMMANDS.len());
+        assert!(help.contains(":goto <number> (:g)         Go to the question with this number"));
+        assert!(help.contains(":hint                       Give a hint for the question"));
+    }
+}
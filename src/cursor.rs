@@ -0,0 +1,169 @@
+/// A cursor over the questions of a quiz in the order they are asked.
+///
+/// `next` and `next_back` move the cursor forwards and backwards and return the
+/// index in the quiz file of the question they moved to, `None` means the cursor
+/// is at the end (or start) of the view and did not move. A filter hides
+/// questions from the view, the indexes and positions of the questions that are
+/// still visible do not change.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cursor {
+    /// Question indexes in the order they are asked.
+    order: Vec<usize>,
+    /// Positions in `order` of the visible questions.
+    view: Vec<usize>,
+    /// Index into `view` of the current question.
+    at: usize,
+}
+
+impl Cursor {
+    /// A cursor on the first of the questions in `order`.
+    pub fn new(order: Vec<usize>) -> Self {
+        let view = (0..order.len()).collect();
+        Self { order, view, at: 0 }
+    }
+
+    /// Index in the quiz file of the current question, `None` when no question is visible.
+    pub fn current(&self) -> Option<usize> {
+        self.current_position().map(|x| self.order[x])
+    }
+
+    /// Position of the current question in the order the questions are asked.
+    pub fn current_position(&self) -> Option<usize> {
+        self.view.get(self.at).copied()
+    }
+
+    /// Number of questions, whether they are visible or not.
+    pub fn total(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Number of questions that are visible.
+    pub fn visible(&self) -> usize {
+        self.view.len()
+    }
+
+    /// Moves to the question asked at `position`, unless it is hidden.
+    pub fn seek(&mut self, position: usize) -> Option<usize> {
+        self.at = self.view.iter().position(|x| *x == position)?;
+        self.current()
+    }
+
+    /// Only shows the questions whose index in the quiz file passes `keep`.
+    /// When the current question is hidden the cursor moves to the next
+    /// visible one, or to the last one when there is none after it.
+    pub fn show_only(&mut self, keep: impl Fn(usize) -> bool) {
+        let position = self.current_position();
+        self.view = (0..self.order.len())
+            .filter(|x| keep(self.order[*x]))
+            .collect();
+
+        self.at = match position {
+            Some(position) => self
+                .view
+                .iter()
+                .position(|x| *x >= position)
+                .unwrap_or(self.view.len().saturating_sub(1)),
+            None => 0,
+        };
+    }
+
+    /// Shows every question again.
+    pub fn show_all(&mut self) {
+        self.show_only(|_| true);
+    }
+}
+
+impl Iterator for Cursor {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.at + 1 >= self.view.len() {
+            return None;
+        }
+
+        self.at += 1;
+        self.current()
+    }
+}
+
+impl DoubleEndedIterator for Cursor {
+    fn next_back(&mut self) -> Option<usize> {
+        if self.at == 0 || self.view.is_empty() {
+            return None;
+        }
+
+        self.at -= 1;
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_navigation() {
+        let mut cursor = Cursor::new(vec![2, 0, 1]);
+        assert_eq!(cursor.current(), Some(2));
+        assert_eq!(cursor.current_position(), Some(0));
+        assert_eq!(cursor.total(), 3);
+
+        assert_eq!(cursor.next_back(), None);
+        assert_eq!(cursor.next(), Some(0));
+        assert_eq!(cursor.next(), Some(1));
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.current(), Some(1));
+        assert_eq!(cursor.next_back(), Some(0));
+        assert_eq!(cursor.current_position(), Some(1));
+
+        assert_eq!(cursor.seek(0), Some(2));
+        assert_eq!(cursor.seek(3), None);
+        assert_eq!(cursor.current_position(), Some(0));
+
+        assert_eq!(cursor.clone().collect::<Vec<usize>>(), vec![0, 1]);
+        cursor.seek(2);
+        assert_eq!(cursor.rev().collect::<Vec<usize>>(), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_cursor_empty() {
+        let mut cursor = Cursor::new(vec![]);
+        assert_eq!(cursor.current(), None);
+        assert_eq!(cursor.current_position(), None);
+        assert_eq!(cursor.next(), None);
+        assert_eq!(cursor.next_back(), None);
+        assert_eq!(cursor.seek(0), None);
+    }
+
+    #[test]
+    fn test_cursor_filter() {
+        let cases = vec![
+            ("current stays visible", 1, vec![0, 1, 3], Some(1), 3),
+            ("moves to the next visible", 1, vec![0, 2], Some(2), 2),
+            ("moves back at the end", 3, vec![0, 1], Some(1), 2),
+            ("hides everything", 1, vec![], None, 0),
+        ];
+
+        for (case_name, position, shown, expected, visible) in cases {
+            let mut cursor = Cursor::new(vec![0, 1, 2, 3]);
+            cursor.seek(position);
+            cursor.show_only(|x| shown.contains(&x));
+
+            assert_eq!(cursor.current(), expected, "case: {case_name} failed");
+            assert_eq!(cursor.visible(), visible, "case: {case_name} failed");
+            assert_eq!(cursor.total(), 4, "case: {case_name} failed");
+        }
+
+        let mut cursor = Cursor::new(vec![3, 2, 1, 0]);
+        cursor.show_only(|x| x % 2 == 0);
+        assert_eq!(cursor.current(), Some(2));
+        assert_eq!(cursor.current_position(), Some(1));
+        assert_eq!(cursor.seek(0), None);
+        assert_eq!(cursor.next(), Some(0));
+        assert_eq!(cursor.current_position(), Some(3));
+
+        cursor.show_all();
+        assert_eq!(cursor.current(), Some(0));
+        assert_eq!(cursor.next_back(), Some(1));
+    }
+}
@@ -0,0 +1,581 @@
+use jsonschema::JSONSchema;
+use serde::Serialize;
+
+use crate::quizlit::Quiz;
+use crate::schema;
+
+/// Lines longer than this are cut around the column shown in a snippet.
+const SNIPPET_WIDTH: usize = 80;
+
+/// Where something is in a text, both counted from 1. Columns count
+/// characters, not bytes.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// The location of the byte at `offset` in `text`.
+    fn at(text: &str, offset: usize) -> Self {
+        let before = text.get(..offset).unwrap_or(text);
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+/// Walks JSON text without building values, to find where they start.
+struct Scanner<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl Scanner<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    /// Moves past `byte` when it comes next.
+    fn eat(&mut self, byte: u8) -> Option<()> {
+        self.skip_whitespace();
+        (self.peek()? == byte).then(|| self.position += 1)
+    }
+
+    /// Reads a string, decoding its escapes.
+    fn string(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let start = self.position;
+        self.eat(b'"')?;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => self.position += 2,
+                _ => self.position += 1,
+            }
+        }
+        self.position += 1;
+        serde_json::from_slice(self.text.get(start..self.position)?).ok()
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'"' => self.string().map(drop),
+            b'{' => self.skip_members(b'}', |scanner| {
+                scanner.string()?;
+                scanner.eat(b':')?;
+                scanner.skip_value()
+            }),
+            b'[' => self.skip_members(b']', Self::skip_value),
+            _ => {
+                while self.peek().is_some_and(|x| !b",]} \t\r\n".contains(&x)) {
+                    self.position += 1;
+                }
+                Some(())
+            }
+        }
+    }
+
+    /// Skips an object or an array, calling `member` for each of its members.
+    fn skip_members(&mut self, end: u8, member: impl Fn(&mut Self) -> Option<()>) -> Option<()> {
+        self.position += 1;
+        if self.eat(end).is_some() {
+            return Some(());
+        }
+        loop {
+            member(self)?;
+            if self.eat(b',').is_none() {
+                return self.eat(end);
+            }
+        }
+    }
+
+    /// Moves to the start of the member `token` of the object or array that
+    /// starts at the current position.
+    fn enter(&mut self, token: &str) -> Option<()> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => {
+                self.position += 1;
+                loop {
+                    let key = self.string()?;
+                    self.eat(b':')?;
+                    if key == token {
+                        break;
+                    }
+                    self.skip_value()?;
+                    self.eat(b',')?;
+                }
+            }
+            b'[' => {
+                self.position += 1;
+                for _ in 0..token.parse::<usize>().ok()? {
+                    self.skip_value()?;
+                    self.eat(b',')?;
+                }
+            }
+            _ => return None,
+        }
+
+        self.skip_whitespace();
+        (!matches!(self.peek()?, b'}' | b']')).then_some(())
+    }
+}
+
+/// Where the value at the JSON `pointer` starts in `text`. Values that are
+/// not there, like missing fields, point at the closest value holding them.
+pub fn locate(text: &str, pointer: &str) -> Location {
+    let mut scanner = Scanner {
+        text: text.as_bytes(),
+        position: 0,
+    };
+    scanner.skip_whitespace();
+
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        let before = scanner.position;
+        if scanner.enter(&token).is_none() {
+            scanner.position = before;
+            break;
+        }
+    }
+
+    Location::at(text, scanner.position)
+}
+
+/// How bad a problem is. Errors make a file invalid, warnings do not.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found in a quiz file.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Diagnostic {
+    pub level: Level,
+    /// What found the problem: "syntax", "schema", "quiz", "io" or the id
+    /// of a lint rule.
+    pub code: String,
+    pub message: String,
+    /// The JSON pointer of the value the problem is about.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pointer: Option<String>,
+    #[serde(flatten)]
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    /// A problem with the value at `pointer` in `text`.
+    pub fn new(code: &str, message: String, pointer: &str, text: &str) -> Self {
+        Self {
+            level: Level::Error,
+            code: code.to_string(),
+            message,
+            pointer: Some(pointer.to_string()),
+            location: Some(locate(text, pointer)),
+        }
+    }
+
+    /// A problem with the file as a whole, e.g. that it cannot be read.
+    pub fn file(code: &str, message: String) -> Self {
+        Self {
+            level: Level::Error,
+            code: code.to_string(),
+            message,
+            pointer: None,
+            location: None,
+        }
+    }
+
+    /// Text that is not JSON.
+    pub fn syntax(error: &serde_json::Error) -> Self {
+        let message = error.to_string();
+        let suffix = format!(" at line {} column {}", error.line(), error.column());
+        // serde counts the column before the first character of a line as 0.
+        let location = Location {
+            line: error.line(),
+            column: error.column().max(1),
+        };
+        Self {
+            level: Level::Error,
+            code: "syntax".to_string(),
+            message: message
+                .strip_suffix(&suffix)
+                .unwrap_or(&message)
+                .to_string(),
+            pointer: None,
+            location: Some(location),
+        }
+    }
+
+    /// The diagnostic the way compilers print them, with the line of `text`
+    /// it is about, e.g.
+    ///
+    /// ```text
+    /// error[schema]: "question" is a required property
+    ///  --> quiz.json:3:5
+    ///   |
+    /// 3 |     {"kind": "order"}
+    ///   |     ^
+    ///   = at /questions/0
+    /// ```
+    pub fn render(&self, file: &str, text: &str) -> String {
+        let mut rendered = format!("{}[{}]: {}", self.level, self.code, self.message);
+        let Some(location) = self.location else {
+            rendered.push_str(&format!("\n --> {file}"));
+            return rendered;
+        };
+
+        let number = location.line.to_string();
+        let gutter = " ".repeat(number.len());
+        rendered.push_str(&format!(
+            "\n{gutter}--> {file}:{}:{}",
+            location.line, location.column
+        ));
+        if let Some(line) = text.lines().nth(location.line - 1) {
+            let (snippet, caret) = snippet(line, location.column);
+            rendered.push_str(&format!(
+                "\n{gutter} |\n{number} | {snippet}\n{gutter} | {}^",
+                " ".repeat(caret)
+            ));
+        }
+        if let Some(pointer) = self.pointer.as_deref().filter(|x| !x.is_empty()) {
+            rendered.push_str(&format!("\n{gutter} = at {pointer}"));
+        }
+        rendered
+    }
+}
+
+/// `line` cut down to fit around `column`, and how far the caret pointing
+/// at `column` is from its start.
+fn snippet(line: &str, column: usize) -> (String, usize) {
+    // Tabs are shown as single spaces to keep the caret under its column.
+    let chars: Vec<char> = line
+        .chars()
+        .map(|x| if x == '\t' { ' ' } else { x })
+        .collect();
+    let index = column.saturating_sub(1).min(chars.len());
+    if chars.len() <= SNIPPET_WIDTH {
+        return (chars.iter().collect(), index);
+    }
+
+    let start = index
+        .saturating_sub(SNIPPET_WIDTH / 3)
+        .min(chars.len() - SNIPPET_WIDTH);
+    let end = start + SNIPPET_WIDTH;
+    let mut snippet = String::new();
+    let mut caret = index - start;
+    if start > 0 {
+        snippet.push_str("...");
+        caret += 3;
+    }
+    snippet.extend(&chars[start..end]);
+    if end < chars.len() {
+        snippet.push_str("...");
+    }
+    (snippet, caret)
+}
+
+/// Everything wrong with the quiz in `text`: that it is not JSON, else
+/// where it does not match `schema`, else what keeps its questions from
+/// loading.
+pub fn check(text: &str, schema: &JSONSchema) -> Vec<Diagnostic> {
+    let instance: serde_json::Value = match serde_json::from_str(text) {
+        Ok(instance) => instance,
+        Err(error) => return vec![Diagnostic::syntax(&error)],
+    };
+
+    if let Err(errors) = schema::validate_json(schema, &instance) {
+        return errors
+            .map(|x| Diagnostic::new("schema", x.to_string(), &x.instance_path.to_string(), text))
+            .collect();
+    }
+
+    match Quiz::new(&instance) {
+        Ok(_) => Vec::new(),
+        Err(error) => vec![Diagnostic::new(
+            "quiz",
+            error.message(),
+            error.pointer(),
+            text,
+        )],
+    }
+}
+
+/// How a file checked by the validate command went.
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub file: String,
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl FileReport {
+    pub fn new(file: String, diagnostics: Vec<Diagnostic>) -> Self {
+        Self {
+            file,
+            valid: diagnostics.iter().all(|x| x.level != Level::Error),
+            diagnostics,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUIZ: &str = r#"{
+  "title": "One Piece",
+  "questions": [
+    {"kind": "order"},
+    {
+      "kind": "user_input",
+      "question": "What is the name \"Luffy\" uses?",
+      "answers": {"accepted": ["Lucy"], "a/b": 1}
+    }
+  ]
+}"#;
+
+    #[test]
+    fn test_locate() {
+        let cases = vec![
+            ("root", "", (1, 1)),
+            ("title", "/title", (2, 12)),
+            ("array", "/questions", (3, 16)),
+            ("first item", "/questions/0", (4, 5)),
+            ("second item", "/questions/1", (5, 5)),
+            ("after an escaped quote", "/questions/1/answers", (8, 18)),
+            ("array item", "/questions/1/answers/accepted/0", (8, 32)),
+            ("escaped token", "/questions/1/answers/a~1b", (8, 48)),
+            ("missing field", "/questions/0/question", (4, 5)),
+            ("missing item", "/questions/5/kind", (3, 16)),
+        ];
+
+        for (case_name, pointer, (line, column)) in cases {
+            assert_eq!(
+                locate(QUIZ, pointer),
+                Location { line, column },
+                "case: {case_name} failed"
+            );
+        }
+
+        assert_eq!(
+            locate("{\"é\": 1, \"b\": 2}", "/b"),
+            Location {
+                line: 1,
+                column: 15
+            }
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let compiled = schema::compile_json_schema(&schema::bundled_schema()).unwrap();
+
+        let cases = vec![
+            ("valid", r#"{"questions": []}"#, vec![]),
+            (
+                "syntax",
+                "{\n  \"questions\": [\n}",
+                vec![("syntax", "expected value".to_string(), None, Some((3, 1)))],
+            ),
+            (
+                "unfinished",
+                "{\"questions\": [\n",
+                vec![(
+                    "syntax",
+                    "EOF while parsing a list".to_string(),
+                    None,
+                    Some((2, 1)),
+                )],
+            ),
+            (
+                "schema",
+                QUIZ,
+                vec![
+                    (
+                        "schema",
+                        "\"question\" is a required property".to_string(),
+                        Some("/questions/0"),
+                        Some((4, 5)),
+                    ),
+                    (
+                        "schema",
+                        "\"answers\" is a required property".to_string(),
+                        Some("/questions/0"),
+                        Some((4, 5)),
+                    ),
+                ],
+            ),
+            (
+                "kind",
+                r#"{"questions": [{"kind": "order", "question": "Order", "answers": {"correct": [{"answer": "a"}]}}, {"kind": "trivia"}]}"#,
+                vec![(
+                    "schema",
+                    "\"trivia\" is not one of [\"true_false\",\"selection\",\"user_input\",\"order\"]"
+                        .to_string(),
+                    Some("/questions/1/kind"),
+                    Some((1, 108)),
+                )],
+            ),
+        ];
+
+        for (case_name, text, expected) in cases {
+            let diagnostics: Vec<_> = check(text, &compiled)
+                .into_iter()
+                .map(|x| {
+                    (
+                        x.code.clone(),
+                        x.message.clone(),
+                        x.pointer.clone(),
+                        x.location.map(|x| (x.line, x.column)),
+                    )
+                })
+                .collect();
+            let expected: Vec<_> = expected
+                .into_iter()
+                .map(|(code, message, pointer, location)| {
+                    (
+                        code.to_string(),
+                        message,
+                        pointer.map(String::from),
+                        location,
+                    )
+                })
+                .collect();
+            assert_eq!(diagnostics, expected, "case: {case_name} failed");
+        }
+
+        // Custom schemas may let through what the questions cannot be loaded from.
+        let permissive = schema::compile_json_schema(&serde_json::json!({})).unwrap();
+        let text = "{\"questions\": [\n  {\"kind\": \"order\", \"question\": \"Order\"}\n]}";
+        assert_eq!(
+            check(text, &permissive),
+            vec![Diagnostic {
+                level: Level::Error,
+                code: "quiz".to_string(),
+                message: "missing required field".to_string(),
+                pointer: Some("/questions/0/answers".to_string()),
+                location: Some(Location { line: 2, column: 3 }),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let text = "{\n\t\"questions\": [\n    {\"kind\": \"order\"}\n  ]\n}";
+        let long = format!("{{\"questions\": [{}{{}}]}}", "{}, ".repeat(40));
+
+        let cases = vec![
+            (
+                "snippet",
+                Diagnostic::new("schema", "wrong".to_string(), "/questions/0", text),
+                text.to_string(),
+                vec![
+                    "error[schema]: wrong",
+                    " --> quiz.json:3:5",
+                    "  |",
+                    "3 |     {\"kind\": \"order\"}",
+                    "  |     ^",
+                    "  = at /questions/0",
+                ],
+            ),
+            (
+                "tab",
+                Diagnostic::new("schema", "wrong".to_string(), "/questions", text),
+                text.to_string(),
+                vec![
+                    "error[schema]: wrong",
+                    " --> quiz.json:2:15",
+                    "  |",
+                    "2 |  \"questions\": [",
+                    "  |               ^",
+                    "  = at /questions",
+                ],
+            ),
+            (
+                "long line",
+                Diagnostic::new("schema", "wrong".to_string(), "/questions/40", &long),
+                long.clone(),
+                vec![
+                    "error[schema]: wrong",
+                    " --> quiz.json:1:176",
+                    "  |",
+                    "1 | ...{}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}, {}]}",
+                    "  |                                                                                ^",
+                    "  = at /questions/40",
+                ],
+            ),
+            (
+                "no location",
+                Diagnostic::file("io", "Unable to read it".to_string()),
+                String::new(),
+                vec!["error[io]: Unable to read it", " --> quiz.json"],
+            ),
+        ];
+
+        for (case_name, diagnostic, text, expected) in cases {
+            assert_eq!(
+                diagnostic.render("quiz.json", &text),
+                expected.join("\n"),
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_report_json() {
+        let report = FileReport::new(
+            "quiz.json".to_string(),
+            vec![
+                Diagnostic::new("schema", "wrong".to_string(), "/questions", QUIZ),
+                Diagnostic::file("io", "Unable to read it".to_string()),
+            ],
+        );
+
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "file": "quiz.json",
+                "valid": false,
+                "diagnostics": [
+                    {
+                        "level": "error",
+                        "code": "schema",
+                        "message": "wrong",
+                        "pointer": "/questions",
+                        "line": 3,
+                        "column": 16
+                    },
+                    {"level": "error", "code": "io", "message": "Unable to read it"}
+                ]
+            })
+        );
+
+        let warning = Diagnostic {
+            level: Level::Warning,
+            ..Diagnostic::new("duplicate-question", "again".to_string(), "", QUIZ)
+        };
+        assert!(FileReport::new("quiz.json".to_string(), vec![warning]).valid);
+    }
+}
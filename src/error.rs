@@ -0,0 +1,164 @@
+use std::fmt;
+
+/// Problems found while turning quiz JSON into questions. Every error carries
+/// the JSON pointer of the offending value, e.g. `/questions/7/answers/correct`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum QuizlitError {
+    MissingField { pointer: String },
+    WrongType { pointer: String, expected: String },
+    UnknownKind { pointer: String, kind: String },
+    EmptyAnswers { pointer: String },
+    Invalid { pointer: String, message: String },
+}
+
+impl QuizlitError {
+    pub fn pointer(&self) -> &str {
+        match self {
+            Self::MissingField { pointer }
+            | Self::WrongType { pointer, .. }
+            | Self::UnknownKind { pointer, .. }
+            | Self::EmptyAnswers { pointer }
+            | Self::Invalid { pointer, .. } => pointer,
+        }
+    }
+
+    /// Turns a serde error into a `QuizlitError`, `pointer` is where the
+    /// deserialized value lives in the quiz document.
+    pub fn from_serde(pointer: &str, error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let mut pointer = pointer.to_string();
+        for segment in error.path() {
+            match segment {
+                serde_path_to_error::Segment::Seq { index } => {
+                    pointer.push_str(&format!("/{index}"));
+                }
+                serde_path_to_error::Segment::Map { key } => push_token(&mut pointer, key),
+                serde_path_to_error::Segment::Enum { .. }
+                | serde_path_to_error::Segment::Unknown => {}
+            }
+        }
+
+        // serde only describes its errors through their messages.
+        let message = error.into_inner().to_string();
+        if let Some(field) = message
+            .strip_prefix("missing field `")
+            .and_then(|x| x.strip_suffix('`'))
+        {
+            push_token(&mut pointer, field);
+            return Self::MissingField { pointer };
+        }
+
+        if let Some((_, expected)) = message
+            .strip_prefix("invalid type: ")
+            .and_then(|x| x.split_once(", expected "))
+        {
+            return Self::WrongType {
+                pointer,
+                expected: expected.to_string(),
+            };
+        }
+
+        Self::Invalid { pointer, message }
+    }
+}
+
+/// Appends a reference token to a JSON pointer, escaping it as RFC 6901 requires.
+pub fn push_token(pointer: &mut String, token: &str) {
+    pointer.push('/');
+    pointer.push_str(&token.replace('~', "~0").replace('/', "~1"));
+}
+
+impl QuizlitError {
+    /// What is wrong, without where.
+    pub fn message(&self) -> String {
+        match self {
+            Self::MissingField { .. } => "missing required field".to_string(),
+            Self::WrongType { expected, .. } => format!("wrong type, expected {expected}"),
+            Self::UnknownKind { kind, .. } => {
+                format!("\"{kind}\" is not a supported question kind")
+            }
+            Self::EmptyAnswers { .. } => "at least one answer is required".to_string(),
+            Self::Invalid { message, .. } => message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for QuizlitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.pointer(), self.message())
+    }
+}
+
+impl std::error::Error for QuizlitError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Data {
+        items: Vec<Item>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        name: String,
+    }
+
+    #[test]
+    fn test_from_serde() {
+        let cases = vec![
+            (
+                "missing field",
+                json!({"items": [{"name": "a"}, {}]}),
+                QuizlitError::MissingField {
+                    pointer: "/questions/3/items/1/name".to_string(),
+                },
+            ),
+            (
+                "wrong type",
+                json!({"items": [{"name": 1}]}),
+                QuizlitError::WrongType {
+                    pointer: "/questions/3/items/0/name".to_string(),
+                    expected: "a string".to_string(),
+                },
+            ),
+        ];
+
+        for (case_name, json, expected) in cases {
+            let error = serde_path_to_error::deserialize::<_, Data>(&json).unwrap_err();
+            assert_eq!(
+                QuizlitError::from_serde("/questions/3", error),
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_push_token() {
+        let cases = vec![("a", "/a"), ("a/b", "/a~1b"), ("m~n", "/m~0n")];
+
+        for (token, expected) in cases {
+            let mut pointer = String::new();
+            push_token(&mut pointer, token);
+            assert_eq!(pointer, expected, "case: {token} failed");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let error = QuizlitError::EmptyAnswers {
+            pointer: "/questions/7/answers/correct".to_string(),
+        };
+
+        assert_eq!(error.pointer(), "/questions/7/answers/correct");
+        assert_eq!(
+            error.to_string(),
+            "/questions/7/answers/correct: at least one answer is required"
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use crate::history;
+use crate::quizlit::{AnswerTrait, Question, QuestionTrait, QuestionType};
+
+/// How well the answer of a flashcard was remembered, as rated by the user
+/// once it is revealed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SelfGrade {
+    Again,
+    Hard,
+    Good,
+    Easy,
+}
+
+/// In the order they are numbered when typed.
+const GRADES: [SelfGrade; 4] = [
+    SelfGrade::Again,
+    SelfGrade::Hard,
+    SelfGrade::Good,
+    SelfGrade::Easy,
+];
+
+impl SelfGrade {
+    /// Parses the number of a grade, "1" to "4", or its name, e.g. "good".
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        GRADES
+            .into_iter()
+            .enumerate()
+            .find(|(number, grade)| {
+                input == (number + 1).to_string() || input.eq_ignore_ascii_case(grade.name())
+            })
+            .map(|(_, grade)| grade)
+    }
+
+    /// How the grade is typed and kept as the answer of a card.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Again => "again",
+            Self::Hard => "hard",
+            Self::Good => "good",
+            Self::Easy => "easy",
+        }
+    }
+
+    /// How much the card counts for in the results, from 0.0 to 1.0.
+    pub fn score(self) -> f32 {
+        match self {
+            Self::Again => 0.0,
+            Self::Hard => 0.5,
+            Self::Good | Self::Easy => 1.0,
+        }
+    }
+
+    pub fn status(self) -> history::Status {
+        match self {
+            Self::Again => history::Status::Incorrect,
+            Self::Hard => history::Status::Partial,
+            Self::Good | Self::Easy => history::Status::Correct,
+        }
+    }
+
+    /// On the SM-2 scale, see `study::quality`.
+    pub fn quality(self) -> u8 {
+        match self {
+            Self::Again => 1,
+            Self::Hard => 3,
+            Self::Good => 4,
+            Self::Easy => 5,
+        }
+    }
+}
+
+/// The two sides of a flashcard.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Card {
+    pub front: String,
+    /// The correct answers, or the question of a reversed card.
+    pub back: Vec<String>,
+    pub explanations: Vec<String>,
+}
+
+impl Card {
+    /// A card with `question` on the front and its correct answers on the
+    /// back. `reversed` swaps them for free text questions, which have a
+    /// single answer to show, other questions are never reversed.
+    pub fn new(question: &Question, reversed: bool) -> Self {
+        let correct = question.get_answers().correct_answers().unwrap_or_default();
+        let explanations = correct.iter().filter_map(|(_, x)| x.clone()).collect();
+        let answers: Vec<String> = correct.into_iter().map(|(x, _)| x).collect();
+
+        match (reversed, question.get_type(), answers.first()) {
+            (true, QuestionType::UserInput, Some(answer)) => Self {
+                front: answer.clone(),
+                back: vec![question.get_question().to_string()],
+                explanations,
+            },
+            _ => Self {
+                front: question.get_question().to_string(),
+                back: answers,
+                explanations,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_self_grade_parse() {
+        let cases = vec![
+            ("1", Some(SelfGrade::Again)),
+            ("2", Some(SelfGrade::Hard)),
+            (" 3 ", Some(SelfGrade::Good)),
+            ("Easy", Some(SelfGrade::Easy)),
+            ("hard", Some(SelfGrade::Hard)),
+            ("5", None),
+            ("0", None),
+            ("", None),
+            ("great", None),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(SelfGrade::parse(input), expected, "case: '{input}' failed");
+        }
+
+        for grade in GRADES {
+            assert_eq!(SelfGrade::parse(grade.name()), Some(grade));
+        }
+    }
+
+    #[test]
+    fn test_card() {
+        let user_input = Question::new(json!({
+            "kind": "user_input",
+            "question": "What is the name Luffy uses in Dressrosa?",
+            "answers": {"accepted": ["Lucy", "Lucy the gladiator"], "explanation": "A disguise"}
+        }))
+        .unwrap();
+        let selection = Question::new(json!({
+            "kind": "selection",
+            "question": "Which are Straw Hats?",
+            "answers": {
+                "correct": [{"answer": "Zoro"}, {"answer": "Nami", "explanation": "The navigator"}],
+                "incorrect": [{"answer": "Buggy"}]
+            }
+        }))
+        .unwrap();
+
+        let cases = vec![
+            (
+                "user_input",
+                &user_input,
+                false,
+                "What is the name Luffy uses in Dressrosa?",
+                vec!["Lucy", "Lucy the gladiator"],
+                vec!["A disguise"],
+            ),
+            (
+                "reversed user_input",
+                &user_input,
+                true,
+                "Lucy",
+                vec!["What is the name Luffy uses in Dressrosa?"],
+                vec!["A disguise"],
+            ),
+            (
+                "selection",
+                &selection,
+                false,
+                "Which are Straw Hats?",
+                vec!["Zoro", "Nami"],
+                vec!["The navigator"],
+            ),
+            (
+                "reversed selection",
+                &selection,
+                true,
+                "Which are Straw Hats?",
+                vec!["Zoro", "Nami"],
+                vec!["The navigator"],
+            ),
+        ];
+
+        for (case_name, question, reversed, front, back, explanations) in cases {
+            let card = Card::new(question, reversed);
+            assert_eq!(card.front, front, "case: {case_name} failed");
+            assert_eq!(card.back, back, "case: {case_name} failed");
+            assert_eq!(card.explanations, explanations, "case: {case_name} failed");
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Whether `path` has wildcards to expand: `*`, `?` or `**`.
+pub fn is_pattern(path: &str) -> bool {
+    path.contains(['*', '?'])
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any characters and
+/// `?` a single one. Like in shells, wildcards do not match the leading
+/// dot of hidden files.
+pub fn matches(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was and how much of the name it took, to take more
+    // of it when what follows does not match.
+    let mut star = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some('?') => (p, n) = (p + 1, n + 1),
+            Some(&c) if c == name[n] => (p, n) = (p + 1, n + 1),
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    (p, n) = (star_p + 1, star_n + 1);
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// The entries of `dir`, the current directory when it is empty.
+fn entries(dir: &Path) -> Vec<(PathBuf, bool)> {
+    let read = match dir.as_os_str().is_empty() {
+        true => std::fs::read_dir("."),
+        false => std::fs::read_dir(dir),
+    };
+    let Ok(read) = read else {
+        return Vec::new();
+    };
+
+    read.filter_map(Result::ok)
+        .map(|entry| {
+            let is_dir = entry.file_type().is_ok_and(|x| x.is_dir());
+            (dir.join(entry.file_name()), is_dir)
+        })
+        .collect()
+}
+
+/// `dir` and every directory under it, hidden ones aside.
+fn walk(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![dir.to_path_buf()];
+    for (path, is_dir) in entries(dir) {
+        let hidden = path
+            .file_name()
+            .is_some_and(|x| x.to_string_lossy().starts_with('.'));
+        if is_dir && !hidden {
+            dirs.extend(walk(&path));
+        }
+    }
+    dirs
+}
+
+/// The files matching `pattern`, sorted. `**` stands for any number of
+/// directories, e.g. `quizzes/**/*.json`.
+pub fn expand(pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in Path::new(pattern).components() {
+        let Component::Normal(part) = component else {
+            paths = paths.iter().map(|x| x.join(component)).collect();
+            continue;
+        };
+        let part = part.to_string_lossy();
+
+        paths = match part.as_ref() {
+            "**" => paths.iter().flat_map(|x| walk(x)).collect(),
+            part if is_pattern(part) => paths
+                .iter()
+                .flat_map(|x| entries(x))
+                .filter(|(path, _)| {
+                    path.file_name()
+                        .is_some_and(|x| matches(part, &x.to_string_lossy()))
+                })
+                .map(|(path, _)| path)
+                .collect(),
+            part => paths.iter().map(|x| x.join(part)).collect(),
+        };
+    }
+
+    paths.retain(|x| x.is_file());
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches() {
+        let cases = vec![
+            ("*.json", "quiz.json", true),
+            ("*.json", "quiz.yaml", false),
+            ("*.json", ".hidden.json", false),
+            (".*.json", ".hidden.json", true),
+            ("quiz?.json", "quiz1.json", true),
+            ("quiz?.json", "quiz.json", false),
+            ("*", "anything", true),
+            ("q*z*.json", "quiz.json", true),
+            ("q*z*.json", "quizz.json", true),
+            ("*a*b", "aXbXab", true),
+            ("*a*b", "aXbXa", false),
+            ("quiz.json", "quiz.json", true),
+            ("quiz.json", "quiz.jsonl", false),
+            ("", "", true),
+        ];
+
+        for (pattern, name, expected) in cases {
+            assert_eq!(
+                matches(pattern, name),
+                expected,
+                "case: {pattern} {name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir =
+            std::env::temp_dir().join(format!("terminal_quizlit-glob-{}", std::process::id()));
+        for file in [
+            "a.json",
+            "b.json",
+            "notes.txt",
+            "one/c.json",
+            "one/two/d.json",
+            ".hidden/e.json",
+        ] {
+            let path = dir.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "{}").unwrap();
+        }
+
+        let cases = vec![
+            ("*.json", vec!["a.json", "b.json"]),
+            ("?.json", vec!["a.json", "b.json"]),
+            ("*/*.json", vec!["one/c.json"]),
+            (
+                "**/*.json",
+                vec!["a.json", "b.json", "one/c.json", "one/two/d.json"],
+            ),
+            ("one/**/*.json", vec!["one/c.json", "one/two/d.json"]),
+            ("notes.txt", vec!["notes.txt"]),
+            ("missing.json", vec![]),
+            ("*.yaml", vec![]),
+        ];
+
+        for (pattern, expected) in cases {
+            let paths = expand(&dir.join(pattern).to_string_lossy());
+            let expected: Vec<PathBuf> = expected.iter().map(|x| dir.join(x)).collect();
+            assert_eq!(paths, expected, "case: {pattern} failed");
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
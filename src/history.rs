@@ -0,0 +1,430 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::quizlit::GradeStatus;
+use crate::save;
+
+/// How many questions `QuizStats` keeps in `most_missed`.
+const MOST_MISSED: usize = 5;
+
+/// How a question was answered in an attempt.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Correct,
+    Partial,
+    Incorrect,
+    Unanswered,
+}
+
+impl From<&GradeStatus> for Status {
+    fn from(status: &GradeStatus) -> Self {
+        match status {
+            GradeStatus::Correct => Self::Correct,
+            GradeStatus::Partial => Self::Partial,
+            GradeStatus::Incorrect => Self::Incorrect,
+        }
+    }
+}
+
+/// A question of an attempt.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Outcome {
+    pub question: String,
+    pub status: Status,
+    pub score: f32,
+    /// Seconds spent on the question.
+    pub time: u64,
+}
+
+/// A quiz that was taken until the end, one line of the history file.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Attempt {
+    /// Where the quiz is loaded from, an absolute path or a URL.
+    pub quiz: String,
+    /// Seconds since the Unix epoch.
+    pub finished_at: u64,
+    pub score: f32,
+    pub total: usize,
+    /// Seconds spent on the whole quiz.
+    pub elapsed: u64,
+    /// In the order the questions were asked.
+    pub questions: Vec<Outcome>,
+}
+
+impl Attempt {
+    pub fn new(quiz: String, finished_at: u64, elapsed: u64, questions: Vec<Outcome>) -> Self {
+        Self {
+            quiz,
+            finished_at,
+            score: questions.iter().map(|x| x.score).sum(),
+            total: questions.len(),
+            elapsed,
+            questions,
+        }
+    }
+
+    /// The score out of 100.
+    pub fn percentage(&self) -> f32 {
+        match self.total {
+            0 => 0.0,
+            total => self.score / total as f32 * 100.0,
+        }
+    }
+
+    /// The questions that were not answered correctly.
+    pub fn missed(&self) -> Vec<&str> {
+        self.questions
+            .iter()
+            .filter(|x| x.status != Status::Correct)
+            .map(|x| x.question.as_str())
+            .collect()
+    }
+}
+
+/// A question that was often not answered correctly.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Missed {
+    pub question: String,
+    /// Attempts in which it was not answered correctly.
+    pub misses: usize,
+    /// Attempts in which it was asked.
+    pub asked: usize,
+}
+
+/// How the attempts at a quiz went.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QuizStats<'a> {
+    pub quiz: &'a str,
+    /// Oldest first.
+    pub attempts: Vec<&'a Attempt>,
+    pub best: &'a Attempt,
+    pub latest: &'a Attempt,
+    /// Average percentage of all the attempts.
+    pub average: f32,
+    /// Most missed first, questions that were never missed are left out.
+    pub most_missed: Vec<Missed>,
+}
+
+impl<'a> QuizStats<'a> {
+    /// `None` when there are no attempts, which are expected to be oldest first.
+    pub fn new(quiz: &'a str, attempts: Vec<&'a Attempt>) -> Option<Self> {
+        let latest = *attempts.last()?;
+        // The earliest of equal attempts is the best, it got there first.
+        let best = attempts
+            .iter()
+            .copied()
+            .rev()
+            .max_by(|a, b| a.percentage().total_cmp(&b.percentage()))?;
+        let average = attempts.iter().map(|x| x.percentage()).sum::<f32>() / attempts.len() as f32;
+
+        let mut missed: BTreeMap<&str, Missed> = BTreeMap::new();
+        for outcome in attempts.iter().flat_map(|x| &x.questions) {
+            let entry = missed.entry(&outcome.question).or_insert_with(|| Missed {
+                question: outcome.question.clone(),
+                misses: 0,
+                asked: 0,
+            });
+            entry.asked += 1;
+            if outcome.status != Status::Correct {
+                entry.misses += 1;
+            }
+        }
+        let mut most_missed: Vec<Missed> = missed.into_values().filter(|x| x.misses > 0).collect();
+        // Sorting is stable, so equally missed questions stay in alphabetical order.
+        most_missed.sort_by_key(|x| std::cmp::Reverse(x.misses));
+        most_missed.truncate(MOST_MISSED);
+
+        Some(Self {
+            quiz,
+            attempts,
+            best,
+            latest,
+            average,
+            most_missed,
+        })
+    }
+
+    /// Percentage points gained between the first and the latest attempt.
+    pub fn improvement(&self) -> f32 {
+        self.latest.percentage() - self.attempts[0].percentage()
+    }
+
+    /// The number of `attempt`, 1 for the first one.
+    pub fn number(&self, attempt: &Attempt) -> usize {
+        self.attempts
+            .iter()
+            .position(|x| std::ptr::eq(*x, attempt))
+            .map_or(0, |x| x + 1)
+    }
+}
+
+/// Groups `attempts` by quiz, sorted by where the quizzes are loaded from.
+pub fn stats(attempts: &[Attempt]) -> Vec<QuizStats<'_>> {
+    let mut quizzes: BTreeMap<&str, Vec<&Attempt>> = BTreeMap::new();
+    for attempt in attempts {
+        quizzes.entry(&attempt.quiz).or_default().push(attempt);
+    }
+
+    quizzes
+        .into_iter()
+        .filter_map(|(quiz, mut attempts)| {
+            attempts.sort_by_key(|x| x.finished_at);
+            QuizStats::new(quiz, attempts)
+        })
+        .collect()
+}
+
+/// Attempt `number` at `quiz`, counting from 1 for the oldest.
+pub fn find<'a>(attempts: &'a [Attempt], quiz: &str, number: u64) -> Result<&'a Attempt, String> {
+    let stats = stats(attempts);
+    let Some(stats) = stats.iter().find(|x| x.quiz == quiz) else {
+        return Err(format!("{quiz} has not been taken until the end yet"));
+    };
+
+    usize::try_from(number)
+        .ok()
+        .and_then(|x| stats.attempts.get(x.wrapping_sub(1)))
+        .copied()
+        .ok_or_else(|| {
+            format!(
+                "There is no attempt {number} at {quiz}, attempts are numbered from 1 to {}",
+                stats.attempts.len()
+            )
+        })
+}
+
+/// Where the attempts are kept, one JSON document per line.
+pub fn history_path() -> Option<PathBuf> {
+    save::data_dir().map(|x| x.join("history.jsonl"))
+}
+
+/// Adds `attempt` at the end of the history file at `path`.
+pub fn append(path: &Path, attempt: &Attempt) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Written in one go, so that a line is never split by another run doing
+    // the same at the same time.
+    let mut line = serde_json::to_string(attempt)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// Every attempt in the history file at `path`, in the order they were added.
+/// Lines that cannot be read are left out.
+pub fn load(path: &Path) -> std::io::Result<Vec<Attempt>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut attempts = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        if let Ok(attempt) = serde_json::from_str(&line?) {
+            attempts.push(attempt);
+        }
+    }
+
+    Ok(attempts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attempt(quiz: &str, finished_at: u64, statuses: Vec<(&str, Status)>) -> Attempt {
+        let questions = statuses
+            .into_iter()
+            .map(|(question, status)| Outcome {
+                question: question.to_string(),
+                status,
+                score: match status {
+                    Status::Correct => 1.0,
+                    Status::Partial => 0.5,
+                    _ => 0.0,
+                },
+                time: 10,
+            })
+            .collect();
+        Attempt::new(quiz.to_string(), finished_at, 30, questions)
+    }
+
+    #[test]
+    fn test_attempt() {
+        let cases = vec![
+            ("empty", vec![], 0.0, 0.0),
+            (
+                "partial",
+                vec![
+                    ("A?", Status::Correct),
+                    ("B?", Status::Partial),
+                    ("C?", Status::Unanswered),
+                    ("D?", Status::Incorrect),
+                ],
+                1.5,
+                37.5,
+            ),
+        ];
+
+        for (case_name, statuses, score, percentage) in cases {
+            let total = statuses.len();
+            let attempt = attempt("quiz.json", 100, statuses);
+            assert_eq!(attempt.score, score, "case: {case_name} failed");
+            assert_eq!(attempt.total, total, "case: {case_name} failed");
+            assert_eq!(attempt.percentage(), percentage, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        let attempts = vec![
+            attempt(
+                "b.json",
+                300,
+                vec![
+                    ("A?", Status::Correct),
+                    ("B?", Status::Incorrect),
+                    ("C?", Status::Correct),
+                    ("D?", Status::Correct),
+                ],
+            ),
+            attempt(
+                "b.json",
+                100,
+                vec![
+                    ("A?", Status::Unanswered),
+                    ("B?", Status::Incorrect),
+                    ("C?", Status::Partial),
+                    ("D?", Status::Correct),
+                ],
+            ),
+            attempt("a.json", 150, vec![("A?", Status::Correct)]),
+            attempt(
+                "b.json",
+                200,
+                vec![
+                    ("A?", Status::Correct),
+                    ("B?", Status::Incorrect),
+                    ("C?", Status::Correct),
+                    ("D?", Status::Correct),
+                ],
+            ),
+        ];
+
+        let stats = stats(&attempts);
+        let quizzes: Vec<&str> = stats.iter().map(|x| x.quiz).collect();
+        assert_eq!(quizzes, vec!["a.json", "b.json"]);
+        assert!(stats[0].most_missed.is_empty());
+        assert_eq!(stats[0].improvement(), 0.0);
+
+        let b = &stats[1];
+        let finished: Vec<u64> = b.attempts.iter().map(|x| x.finished_at).collect();
+        assert_eq!(finished, vec![100, 200, 300]);
+        assert_eq!(b.best.finished_at, 200);
+        assert_eq!(b.number(b.best), 2);
+        assert_eq!(b.latest.finished_at, 300);
+        assert_eq!(b.number(b.latest), 3);
+        assert_eq!(b.average, 62.5);
+        assert_eq!(b.improvement(), 37.5);
+        assert_eq!(
+            b.most_missed,
+            vec![
+                Missed {
+                    question: "B?".to_string(),
+                    misses: 3,
+                    asked: 3,
+                },
+                Missed {
+                    question: "A?".to_string(),
+                    misses: 1,
+                    asked: 3,
+                },
+                Missed {
+                    question: "C?".to_string(),
+                    misses: 1,
+                    asked: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let attempts = vec![
+            attempt(
+                "quiz.json",
+                200,
+                vec![("A?", Status::Correct), ("B?", Status::Partial)],
+            ),
+            attempt(
+                "quiz.json",
+                100,
+                vec![("A?", Status::Unanswered), ("B?", Status::Incorrect)],
+            ),
+        ];
+
+        let cases = vec![
+            ("first", "quiz.json", 1, Ok(vec!["A?", "B?"])),
+            ("second", "quiz.json", 2, Ok(vec!["B?"])),
+            (
+                "no such attempt",
+                "quiz.json",
+                3,
+                Err("There is no attempt 3 at quiz.json, attempts are numbered from 1 to 2"),
+            ),
+            (
+                "zero",
+                "quiz.json",
+                0,
+                Err("There is no attempt 0 at quiz.json, attempts are numbered from 1 to 2"),
+            ),
+            (
+                "other quiz",
+                "other.json",
+                1,
+                Err("other.json has not been taken until the end yet"),
+            ),
+        ];
+
+        for (case_name, quiz, number, expected) in cases {
+            let missed = find(&attempts, quiz, number).map(|x| x.missed());
+            assert_eq!(
+                missed,
+                expected.map_err(String::from),
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let dir =
+            std::env::temp_dir().join(format!("terminal_quizlit-history-{}", std::process::id()));
+        let path = dir.join("history.jsonl");
+        assert_eq!(load(&path).unwrap(), vec![]);
+
+        let first = attempt("quiz.json", 100, vec![("A?", Status::Correct)]);
+        let second = attempt("quiz.json", 200, vec![("A?", Status::Partial)]);
+        append(&path, &first).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+        append(&path, &second).unwrap();
+
+        assert_eq!(load(&path).unwrap(), vec![first, second]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,441 @@
+use crate::diagnostic::{Diagnostic, Level};
+use crate::matcher;
+use crate::quizlit::{AnswerOption, Answers, Question, QuestionTrait, Quiz};
+
+/// A check of what a quiz says, which the schema cannot express.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Rule {
+    pub id: &'static str,
+    pub level: Level,
+    pub description: &'static str,
+}
+
+pub const RULES: [Rule; 6] = [
+    Rule {
+        id: "correct-also-incorrect",
+        level: Level::Error,
+        description: "A selection answer is both correct and incorrect",
+    },
+    Rule {
+        id: "duplicate-option",
+        level: Level::Warning,
+        description: "The same answer is listed twice in a question",
+    },
+    Rule {
+        id: "identical-true-false",
+        level: Level::Error,
+        description: "The two answers of a true/false question are the same",
+    },
+    Rule {
+        id: "single-item-order",
+        level: Level::Warning,
+        description: "An order question has a single item, so it is always answered correctly",
+    },
+    Rule {
+        id: "empty-accepted",
+        level: Level::Error,
+        description: "A free text question accepts no answer, so it is never answered correctly",
+    },
+    Rule {
+        id: "duplicate-question",
+        level: Level::Warning,
+        description: "The same question is asked twice",
+    },
+];
+
+fn rule(id: &str) -> Rule {
+    *RULES
+        .iter()
+        .find(|x| x.id == id)
+        .expect("findings are made by known rules")
+}
+
+/// Something a rule found in a question.
+#[derive(Debug, PartialEq)]
+struct Finding {
+    rule: &'static str,
+    /// Where it is, under the pointer of the question.
+    pointer: String,
+    message: String,
+}
+
+impl Finding {
+    fn new(rule: &'static str, pointer: String, message: String) -> Self {
+        Self {
+            rule,
+            pointer,
+            message,
+        }
+    }
+}
+
+/// How answers are compared to find the same one twice: spaces around them
+/// and case do not matter.
+fn key(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+/// Every item of `keys` equal to an earlier one, with the index of the
+/// earlier one.
+fn repeated(keys: &[String]) -> Vec<(usize, usize)> {
+    keys.iter()
+        .enumerate()
+        .filter_map(|(index, key)| {
+            keys[..index]
+                .iter()
+                .position(|x| x == key)
+                .map(|first| (index, first))
+        })
+        .collect()
+}
+
+/// The answers of `options` that are listed twice, under `pointer`.
+fn duplicate_options(options: &[AnswerOption], pointer: &str) -> Vec<Finding> {
+    let keys: Vec<String> = options.iter().map(|x| key(&x.answer)).collect();
+    repeated(&keys)
+        .into_iter()
+        .map(|(index, first)| {
+            Finding::new(
+                "duplicate-option",
+                format!("{pointer}/{index}"),
+                format!(
+                    "\"{}\" is listed twice, as answer {} and {}",
+                    options[index].answer,
+                    first + 1,
+                    index + 1
+                ),
+            )
+        })
+        .collect()
+}
+
+/// What the rules find in `question` on its own.
+fn check_question(question: &Question) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    match question.get_answers() {
+        Answers::TrueFalse(answers) => {
+            if key(&answers.correct.answer) == key(&answers.incorrect.answer) {
+                findings.push(Finding::new(
+                    "identical-true-false",
+                    "/answers/incorrect".to_string(),
+                    format!(
+                        "The correct and the incorrect answers are both \"{}\"",
+                        answers.incorrect.answer
+                    ),
+                ));
+            }
+        }
+        Answers::Selection(answers) => {
+            findings.extend(duplicate_options(&answers.correct, "/answers/correct"));
+            findings.extend(duplicate_options(&answers.incorrect, "/answers/incorrect"));
+            for (index, incorrect) in answers.incorrect.iter().enumerate() {
+                if answers
+                    .correct
+                    .iter()
+                    .any(|x| key(&x.answer) == key(&incorrect.answer))
+                {
+                    findings.push(Finding::new(
+                        "correct-also-incorrect",
+                        format!("/answers/incorrect/{index}"),
+                        format!(
+                            "\"{}\" is both a correct and an incorrect answer",
+                            incorrect.answer
+                        ),
+                    ));
+                }
+            }
+        }
+        Answers::UserInput(answers) => {
+            if answers.accepted.iter().all(|x| x.trim().is_empty()) {
+                findings.push(Finding::new(
+                    "empty-accepted",
+                    "/answers/accepted".to_string(),
+                    "No answer is accepted, so it can never be answered correctly".to_string(),
+                ));
+            }
+            // Free text is compared the way answers are graded.
+            let keys: Vec<String> = answers
+                .accepted
+                .iter()
+                .map(|x| matcher::normalize(x, answers.case_sensitive))
+                .collect();
+            for (index, first) in repeated(&keys) {
+                findings.push(Finding::new(
+                    "duplicate-option",
+                    format!("/answers/accepted/{index}"),
+                    format!(
+                        "\"{}\" is accepted twice, as answer {} and {}",
+                        answers.accepted[index],
+                        first + 1,
+                        index + 1
+                    ),
+                ));
+            }
+        }
+        Answers::Order(answers) => {
+            if answers.correct.len() == 1 {
+                findings.push(Finding::new(
+                    "single-item-order",
+                    "/answers/correct".to_string(),
+                    "There is a single item to order, so it is always answered correctly"
+                        .to_string(),
+                ));
+            }
+            findings.extend(duplicate_options(&answers.correct, "/answers/correct"));
+        }
+    }
+    findings
+}
+
+/// The rules turned off in the quiz file for the value at `pointer`, listed
+/// in its `lint.allow` field.
+fn allowed_in(instance: &serde_json::Value, pointer: &str) -> Vec<String> {
+    instance
+        .pointer(&format!("{pointer}/lint/allow"))
+        .and_then(|x| x.as_array())
+        .map(|x| {
+            x.iter()
+                .filter_map(|x| x.as_str())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Runs every rule but those in `allow` on the quiz in `text`. Rules can
+/// also be turned off in the quiz itself, for the whole quiz or for one
+/// question, with `"lint": {"allow": ["<rule>"]}`. Quizzes that cannot be
+/// loaded have nothing to lint, `diagnostic::check` reports why.
+pub fn lint(text: &str, allow: &[String]) -> Vec<Diagnostic> {
+    let Ok(instance) = serde_json::from_str::<serde_json::Value>(text) else {
+        return Vec::new();
+    };
+    let Ok(quiz) = Quiz::new(&instance) else {
+        return Vec::new();
+    };
+
+    let mut findings: Vec<(usize, Finding)> = Vec::new();
+    for (index, question) in quiz.questions.iter().enumerate() {
+        findings.extend(check_question(question).into_iter().map(|x| (index, x)));
+    }
+    let questions: Vec<String> = quiz
+        .questions
+        .iter()
+        .map(|x| key(x.get_question()))
+        .collect();
+    for (index, first) in repeated(&questions) {
+        let message = format!("The same question is asked as question {}", first + 1);
+        findings.push((
+            index,
+            Finding::new("duplicate-question", "/question".to_string(), message),
+        ));
+    }
+    findings.sort_by_key(|(index, _)| *index);
+
+    let allowed_in_quiz = allowed_in(&instance, "");
+    findings
+        .into_iter()
+        .filter(|(index, finding)| {
+            let question = format!("/questions/{index}");
+            !allow
+                .iter()
+                .chain(&allowed_in_quiz)
+                .chain(&allowed_in(&instance, &question))
+                .any(|x| x == finding.rule)
+        })
+        .map(|(index, finding)| Diagnostic {
+            level: rule(finding.rule).level,
+            ..Diagnostic::new(
+                finding.rule,
+                finding.message,
+                &format!("/questions/{index}{}", finding.pointer),
+                text,
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn quiz(questions: serde_json::Value) -> String {
+        serde_json::to_string_pretty(&json!({"questions": questions})).unwrap()
+    }
+
+    #[test]
+    fn test_lint() {
+        let selection = |correct: Vec<&str>, incorrect: Vec<&str>| {
+            let options = |x: Vec<&str>| -> Vec<serde_json::Value> {
+                x.into_iter().map(|x| json!({"answer": x})).collect()
+            };
+            json!({
+                "kind": "selection",
+                "question": "Which are Straw Hats?",
+                "answers": {"correct": options(correct), "incorrect": options(incorrect)}
+            })
+        };
+        let user_input = |accepted: Vec<&str>, case_sensitive: bool| {
+            json!({
+                "kind": "user_input",
+                "question": "Who?",
+                "answers": {"accepted": accepted, "caseSensitive": case_sensitive}
+            })
+        };
+
+        let cases = vec![
+            (
+                "clean",
+                json!([
+                    selection(vec!["Zoro"], vec!["Buggy"]),
+                    user_input(vec!["Luffy"], false)
+                ]),
+                vec![],
+            ),
+            (
+                "correct also incorrect",
+                json!([selection(vec!["Zoro", "Nami"], vec!["Buggy", " nami "])]),
+                vec![("correct-also-incorrect", "/questions/0/answers/incorrect/1")],
+            ),
+            (
+                "duplicate options",
+                json!([selection(vec!["Zoro", "zoro"], vec!["Buggy", "Buggy"])]),
+                vec![
+                    ("duplicate-option", "/questions/0/answers/correct/1"),
+                    ("duplicate-option", "/questions/0/answers/incorrect/1"),
+                ],
+            ),
+            (
+                "identical true/false",
+                json!([{
+                    "kind": "true_false",
+                    "question": "Is it?",
+                    "answers": {"correct": {"answer": "True"}, "incorrect": {"answer": "true"}}
+                }]),
+                vec![("identical-true-false", "/questions/0/answers/incorrect")],
+            ),
+            (
+                "single item order",
+                json!([{
+                    "kind": "order",
+                    "question": "Order",
+                    "answers": {"correct": [{"answer": "Alabasta"}]}
+                }]),
+                vec![("single-item-order", "/questions/0/answers/correct")],
+            ),
+            (
+                "empty accepted",
+                json!([user_input(vec![], false), user_input(vec![" "], false)]),
+                vec![
+                    ("empty-accepted", "/questions/0/answers/accepted"),
+                    ("empty-accepted", "/questions/1/answers/accepted"),
+                    ("duplicate-question", "/questions/1/question"),
+                ],
+            ),
+            (
+                "accepted twice once normalized",
+                json!([user_input(vec!["Luffy", "luffy!"], false)]),
+                vec![("duplicate-option", "/questions/0/answers/accepted/1")],
+            ),
+            (
+                "accepted case sensitive",
+                json!([user_input(vec!["Luffy", "luffy"], true)]),
+                vec![],
+            ),
+            (
+                "duplicate questions",
+                json!([
+                    user_input(vec!["Luffy"], false),
+                    selection(vec!["Zoro"], vec!["Buggy"]),
+                    user_input(vec!["Zoro"], false)
+                ]),
+                vec![("duplicate-question", "/questions/2/question")],
+            ),
+        ];
+
+        for (case_name, questions, expected) in cases {
+            let diagnostics: Vec<(String, Option<String>)> = lint(&quiz(questions), &[])
+                .into_iter()
+                .map(|x| (x.code, x.pointer))
+                .collect();
+            let expected: Vec<(String, Option<String>)> = expected
+                .into_iter()
+                .map(|(code, pointer)| (code.to_string(), Some(pointer.to_string())))
+                .collect();
+            assert_eq!(diagnostics, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_lint_diagnostic() {
+        let text = quiz(json!([{
+            "kind": "order",
+            "question": "Order",
+            "answers": {"correct": [{"answer": "Alabasta"}, {"answer": "Alabasta"}]}
+        }]));
+
+        let diagnostics = lint(&text, &[]);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].level, Level::Warning);
+        assert_eq!(
+            diagnostics[0].message,
+            "\"Alabasta\" is listed twice, as answer 1 and 2"
+        );
+        let location = diagnostics[0].location.unwrap();
+        assert_eq!((location.line, location.column), (11, 11));
+    }
+
+    #[test]
+    fn test_lint_allow() {
+        let questions = json!([
+            {
+                "kind": "user_input",
+                "question": "Who?",
+                "answers": {"accepted": []},
+                "lint": {"allow": ["empty-accepted"]}
+            },
+            {"kind": "user_input", "question": "Who?", "answers": {"accepted": []}}
+        ]);
+
+        let cases = vec![
+            (
+                "per question",
+                json!({"questions": questions}),
+                vec![],
+                vec!["duplicate-question", "empty-accepted"],
+            ),
+            (
+                "on the command line",
+                json!({"questions": questions}),
+                vec!["duplicate-question".to_string()],
+                vec!["empty-accepted"],
+            ),
+            (
+                "for the whole quiz",
+                json!({"lint": {"allow": ["empty-accepted"]}, "questions": questions}),
+                vec![],
+                vec!["duplicate-question"],
+            ),
+        ];
+
+        for (case_name, instance, allow, expected) in cases {
+            let text = serde_json::to_string(&instance).unwrap();
+            let mut codes: Vec<String> = lint(&text, &allow).into_iter().map(|x| x.code).collect();
+            codes.sort();
+            assert_eq!(codes, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_rules() {
+        for (index, rule) in RULES.iter().enumerate() {
+            assert!(
+                RULES[..index].iter().all(|x| x.id != rule.id),
+                "{} is defined twice",
+                rule.id
+            );
+        }
+        assert!(lint("not json", &[]).is_empty());
+    }
+}
@@ -1,214 +1,655 @@
+mod author;
+mod cli;
+mod command;
+mod cursor;
+mod diagnostic;
+mod error;
+mod flashcard;
+mod glob;
+mod history;
+mod lint;
+mod matcher;
 mod quizlit;
 mod requests;
+mod save;
 mod schema;
+mod session;
+mod source;
+mod study;
 mod template;
+mod tui;
 
-use std::collections::HashMap;
-use std::io::Write;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::process::ExitCode;
 
 use askama::Template;
+use clap::Parser;
 
-use crate::quizlit::{AnswerTrait, QuestionTrait, QuestionType};
+use crate::quizlit::QuestionTrait;
+use crate::session::Input;
+use crate::source::Source;
 
-/*
-Thoughts on the display:
+const NO_DATA_DIR: &str =
+    "Unable to find where to keep data between runs, set XDG_DATA_HOME or HOME";
 
-I think I can build the display content from wrapped double ended iterators.
-The inner iterator is used for question and answer text and display count (they know the total and index).
-The next layer of iterator handles the user input interface layer (commands and accepting answers).
+/// Clears the terminal. Terminals that can not be cleared, e.g. with
+/// TERM=dumb, get a blank line between screens instead.
+fn clear_screen() {
+    if clearscreen::clear().is_err() {
+        println!();
+    }
+}
 
-Anyway, this will make everything disconnected, testable in isolation and
-the main function will only have to deal with a single iterator.
-*/
+/// Draws session events on the terminal.
+struct Terminal;
+
+impl session::Output for Terminal {
+    fn render(&mut self, event: &session::Event) -> std::io::Result<()> {
+        match event {
+            session::Event::Question {
+                notice,
+                text,
+                time_left,
+                ..
+            }
+            | session::Event::Review {
+                notice,
+                text,
+                time_left,
+            } => {
+                clear_screen();
+                if let Some(time_left) = time_left {
+                    println!("Time: {time_left}\n");
+                }
+                if let Some(notice) = notice {
+                    println!("{notice}\n");
+                }
+                print!("{text}");
+            }
+            session::Event::Feedback { text } => {
+                clear_screen();
+                print!("{text}");
+            }
+            session::Event::Results { text } | session::Event::Saved { text } => {
+                println!("{text}")
+            }
+        }
 
-// TODO: Make this into a double ended iterator
-struct QuestionListDisplay {
-    questions: Vec<quizlit::Question>,
-    index: usize,
+        // Needed to make sure that the text right before the user input
+        // gets written to the screen.
+        std::io::stdout().flush()
+    }
 }
 
-impl QuestionListDisplay {
-    fn new(questions: Vec<quizlit::Question>) -> Self {
-        Self {
-            questions,
-            index: 0,
+/// Whether the quiz is shown full screen. The full screen interface needs
+/// a terminal to read keys from and to draw on.
+fn full_screen(interface: cli::Interface) -> bool {
+    match interface {
+        cli::Interface::Tui => true,
+        cli::Interface::Line => false,
+        cli::Interface::Auto => {
+            std::io::stdin().is_terminal()
+                && std::io::stdout().is_terminal()
+                && std::env::var("TERM").map_or(true, |x| x != "dumb")
         }
     }
+}
 
-    fn current_index(&self) -> usize {
-        self.index
+/// Opens the stream user answers are read from. When the quiz itself was
+/// piped through stdin, answers come from the terminal instead.
+fn open_input(quiz: &Source) -> std::io::Result<Box<dyn BufRead + Send>> {
+    if *quiz != Source::Stdin {
+        return Ok(Box::new(BufReader::new(std::io::stdin())));
     }
 
-    fn previous(&mut self) -> Option<String> {
-        match self.index > 0 {
-            true => {
-                self.index -= 1;
-                Some(self.current_question())
+    #[cfg(windows)]
+    let terminal = std::fs::File::open("CONIN$")?;
+    #[cfg(not(windows))]
+    let terminal = std::fs::File::open("/dev/tty")?;
+
+    Ok(Box::new(BufReader::new(terminal)))
+}
+
+/// Compiles the schema from `schema`, the bundled one when it is `None`.
+async fn compile_schema(
+    schema: Option<Source>,
+) -> Result<jsonschema::JSONSchema, Box<dyn std::error::Error>> {
+    let compiled = match schema {
+        Some(source) => {
+            let schema = source.load().await?;
+            let mut resolver = schema::OfflineResolver::new();
+            if let Source::Url(url) = &source {
+                resolver
+                    .fetch_references(&schema, &url::Url::parse(url)?)
+                    .await?;
             }
-            false => None,
+            schema::compile_json_schema_with(&schema, resolver)
+                .map_err(|e| format!("Unable to compile the schema {source}: {e}"))?
         }
+        None => schema::compile_json_schema(&schema::bundled_schema())
+            .expect("Compiling the bundled schema should not fail"),
+    };
+    Ok(compiled)
+}
+
+/// Checks the quiz in `text`, read from `file`, against the schema from
+/// `schema` and prints what is wrong with it. Returns whether it is valid.
+async fn check(
+    text: &str,
+    file: &str,
+    schema: Option<Source>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let compiled = compile_schema(schema).await?;
+    let diagnostics = diagnostic::check(text, &compiled);
+    for diagnostic in &diagnostics {
+        eprintln!("{}\n", diagnostic.render(file, text));
     }
 
-    fn next(&mut self) -> Option<String> {
-        match self.index < self.questions.len() - 1 {
-            true => {
-                self.index += 1;
-                Some(self.current_question())
-            }
-            false => None,
-        }
-    }
-    fn current_question(&self) -> String {
-        let question = &self.questions[self.index];
-        let question_type = question.get_type();
-        let question_str = question.get_question().unwrap();
-
-        let question_text = match question_type {
-            QuestionType::TrueFalse => {
-                let answers = question.get_answers().unwrap().answers().unwrap();
-                let question_template = template::TrueFalseQuestionTemplate::new(
-                    &question_str,
-                    &answers[0],
-                    &answers[1],
-                );
-                question_template.render().unwrap()
-            }
-            QuestionType::Selection => {
-                let answers = question.get_answers().unwrap().answers().unwrap();
-                let mut answers_str = Vec::new();
-                for x in &answers {
-                    answers_str.push(x.as_str());
-                }
+    Ok(diagnostics.is_empty())
+}
 
-                let question_template =
-                    template::SelectionQuestionTemplate::new(&question_str, answers_str);
-                question_template.render().unwrap()
-            }
-            QuestionType::UserInput => {
-                let question_template = template::UserInputQuestionTemplate::new(&question_str);
-                question_template.render().unwrap()
-            }
-            QuestionType::Order => {
-                let answers = question.get_answers().unwrap().answers().unwrap();
-                let mut answers_str = Vec::new();
-                for x in &answers {
-                    answers_str.push(x.as_str());
-                }
+/// The questions of `instance`, `None` once it is printed why there are none.
+fn questions(instance: &serde_json::Value) -> Option<Vec<quizlit::Question>> {
+    match quizlit::Quiz::new(instance) {
+        Ok(quiz) => Some(quiz.questions),
+        Err(error) => {
+            eprintln!("Invalid quiz: {error}");
+            None
+        }
+    }
+}
 
-                let question_template =
-                    template::SelectionQuestionTemplate::new(&question_str, answers_str);
-                question_template.render().unwrap()
-            }
-        };
-        question_text
+/// Names the quiz loaded from `source` so that it can be loaded again later,
+/// `None` when it cannot.
+fn source_name(source: &Source) -> Option<String> {
+    match source {
+        Source::Path(path) => Some(
+            std::fs::canonicalize(path)
+                .unwrap_or_else(|_| path.clone())
+                .display()
+                .to_string(),
+        ),
+        Source::Url(url) => Some(url.clone()),
+        Source::Stdin => None,
     }
 }
 
-#[derive(PartialEq)]
-enum Command {
-    Next,
-    Previous,
-    Quit,
-    Unknown,
-    Answer,
+/// Adds the attempt `session` made at the quiz loaded from `source` to the
+/// history, once it was submitted. Sessions cut short by the input running
+/// out are left to be resumed and recorded then.
+fn record(session: &session::QuizSession, source: String) {
+    if !session.is_submitted() {
+        return;
+    }
+
+    let attempt = history::Attempt::new(
+        source,
+        save::now(),
+        session.elapsed().as_secs(),
+        session.outcomes(),
+    );
+    let result = history::history_path()
+        .ok_or_else(|| NO_DATA_DIR.to_string())
+        .and_then(|path| history::append(&path, &attempt).map_err(|e| e.to_string()));
+    if let Err(error) = result {
+        eprintln!("Unable to keep the results in the history: {error}");
+    }
 }
 
-fn parse_command(input: &str) -> Command {
-    match input {
-        "n" | "next" => Command::Next,
-        "p" | "previous" => Command::Previous,
-        "q" | "Quit" => Command::Quit,
-        _ => Command::Answer,
+/// Takes the quiz loaded from `quiz` through `session` until it is over.
+/// Answers typed on the line interface are read from `input`, which is
+/// opened the first time and then kept, so that the lines it read ahead
+/// are not lost between rounds.
+async fn take(
+    session: &mut session::QuizSession,
+    interface: cli::Interface,
+    input: &mut Option<session::BackgroundInput>,
+    quiz: &Source,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match full_screen(interface) {
+        true => println!("{}", tui::run(session)?),
+        false => {
+            let input = match input {
+                Some(input) => input,
+                None => input.insert(session::BackgroundInput::new(open_input(quiz)?)),
+            };
+            session.run(input, &mut Terminal).await?
+        }
+    }
+
+    if let Some(store) = session.store().filter(|x| x.exists()) {
+        eprintln!(
+            "The session is saved, go on with it with `terminal_quizlit resume {}`",
+            store.id()
+        );
     }
+    Ok(())
 }
 
-fn get_questions(json: &serde_json::Value) -> Result<Vec<quizlit::Question>, String> {
-    let mut result = Vec::new();
-    let json_questions = json["questions"].as_array().expect("valid json");
-    for data in json_questions {
-        match quizlit::Question::new(data.clone()) {
-            Ok(question) => result.push(question),
-            Err(_) => return Err(format!("Unable to create question from {:?}", data)),
+/// Asks `question` and returns whether the answer is yes. Running out of
+/// input counts as no.
+async fn confirm(
+    question: &str,
+    input: &mut Option<session::BackgroundInput>,
+    quiz: &Source,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    print!("\n{question} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let line = match input {
+        Some(input) => input.read_line().await?,
+        None => Input::read_line(&mut open_input(quiz)?).await?,
+    };
+    Ok(line.is_some_and(|x| matches!(x.trim().to_lowercase().as_str(), "y" | "yes")))
+}
+
+/// Asks the questions `session` missed again, in rounds, until they are all
+/// answered correctly or the user stops.
+async fn retry(
+    mut session: session::QuizSession,
+    shuffle: bool,
+    interface: cli::Interface,
+    input: &mut Option<session::BackgroundInput>,
+    quiz: &Source,
+) -> Result<(), Box<dyn std::error::Error>> {
+    while session.is_submitted() {
+        let Some(next) = session.retry(rand::random(), shuffle) else {
+            if session.round() > 1 {
+                println!("\nEverything was answered correctly");
+            }
+            break;
+        };
+
+        let question = match next.outcomes().len() {
+            1 => "Retry the missed question?".to_string(),
+            count => format!("Retry the {count} missed questions?"),
+        };
+        if !confirm(&question, input, quiz).await? {
+            break;
         }
+        session = next;
+        take(&mut session, interface, input, quiz).await?;
     }
 
-    Ok(result)
+    Ok(())
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let schema = requests::get_json(
-        "https://raw.githubusercontent.com/Quizlit/schemas/main/src/schemas/v1/quizlit.json",
-    )
-    .await?;
+async fn run(args: cli::RunArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let text = args.quiz.read().await?;
+    if !args.no_validate && !check(&text, &args.quiz.to_string(), args.schema_source()).await? {
+        return Ok(ExitCode::from(cli::EXIT_VALIDATION_ERROR));
+    }
+    let instance = serde_json::from_str(&text)?;
+    let Some(mut list) = questions(&instance) else {
+        return Ok(ExitCode::from(cli::EXIT_VALIDATION_ERROR));
+    };
+    let name = source_name(&args.quiz);
+
+    let mut deck = None;
+    if args.study {
+        let source = name
+            .clone()
+            .ok_or("Studying needs a quiz file or URL, the schedule is kept per quiz")?;
+        let dir = study::decks_dir().ok_or(NO_DATA_DIR)?;
+        let loaded = study::load(&dir, &source)?;
+        let today = study::today();
+        let all: Vec<String> = list.iter().map(|x| x.get_question().to_string()).collect();
+
+        list.retain(|x| loaded.is_due(x.get_question(), today));
+        if list.is_empty() {
+            println!("Nothing is due: {}", loaded.summary(&all, today));
+            return Ok(ExitCode::SUCCESS);
+        }
+        deck = Some((dir, loaded, all));
+    }
+    if let Some(number) = args.only_missed {
+        let source = name
+            .clone()
+            .ok_or("Quizzes read from stdin have no history")?;
+        let attempts = history::load(&history::history_path().ok_or(NO_DATA_DIR)?)?;
+        let missed = history::find(&attempts, &source, number)?.missed();
+        if missed.is_empty() {
+            println!("Nothing was missed in attempt {number}");
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        list.retain(|x| missed.contains(&x.get_question()));
+        if list.is_empty() {
+            println!("The questions missed in attempt {number} are not in the quiz anymore");
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+    // Study sessions and retries ask only part of the quiz.
+    let partial = args.study || args.only_missed.is_some();
+
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let layout = quizlit::Layout::new(&list, seed, !args.no_shuffle, args.shuffle_questions);
+    let options = quizlit::GradeOptions {
+        scoring: args.scoring,
+        typo_tolerance: args.typo_tolerance,
+    };
+    // Partial sessions cannot be resumed.
+    let quiz = name
+        .clone()
+        .filter(|_| !args.no_save && !partial)
+        .map(|x| save::QuizIdentity::new(x, &instance, &list));
+    let limits = session::TimeLimits {
+        quiz: args.time_limit,
+        question: args.question_time_limit,
+    };
+    let mut session = session::QuizSession::new(list, layout, seed, options, args.practice)
+        .with_time_limits(limits)
+        .with_mode(args.mode());
+
+    if let (Some(quiz), Some(dir)) = (quiz, save::sessions_dir()) {
+        session = session.with_store(save::Store::new(&dir, &quiz, seed), quiz);
+    }
+    let mut input = None;
+    take(&mut session, args.interface, &mut input, &args.quiz).await?;
 
-    let instance = requests::get_json(
-        "https://raw.githubusercontent.com/Quizlit/schemas/main/src/schemas/v1/examples/20_questions.json",
+    // The scores of partial sessions do not compare with the others.
+    if let Some(name) = name.filter(|_| !partial) {
+        record(&session, name);
+    }
+    if let Some((dir, mut deck, all)) = deck {
+        let today = study::today();
+        for (question, quality) in session.recall() {
+            deck.record(question.get_question(), quality, today);
+        }
+        study::write(&dir, &deck)?;
+        println!("Study: {}", deck.summary(&all, today));
+    }
+    retry(
+        session,
+        !args.no_shuffle,
+        args.interface,
+        &mut input,
+        &args.quiz,
     )
     .await?;
 
-    let compiled =
-        schema::compile_json_schema(&schema).expect("Compiling the schema should not fail");
-    let result = schema::validate_json(&compiled, &instance);
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Prints how many questions are due for every quiz that was studied.
+async fn due() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let decks = study::list(&study::decks_dir().ok_or(NO_DATA_DIR)?)?;
+    if decks.is_empty() {
+        println!("No quiz was studied yet, start with `terminal_quizlit run <quiz> --study`");
+    }
+
+    let today = study::today();
+    for deck in decks {
+        // The questions never studied are due too, they are only in the quiz.
+        let loaded = match Source::parse(&deck.source) {
+            Ok(source) => source.load().await,
+            Err(error) => Err(error.into()),
+        };
+        let summary = match loaded.map(|x| quizlit::Quiz::new(&x).map(|x| x.questions)) {
+            Ok(Ok(questions)) => {
+                let all: Vec<String> = questions
+                    .iter()
+                    .map(|x| x.get_question().to_string())
+                    .collect();
+                deck.summary(&all, today)
+            }
+            Ok(Err(error)) => format!("Invalid quiz: {error}"),
+            Err(error) => error.to_string(),
+        };
+        println!("{}\n    {summary}", deck.source);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
 
-    if let Err(errors) = result {
-        for error in errors {
-            println!("Validatation error: {}", error);
-            println!("Instance path: {}", error.instance_path);
+/// Prints how the attempts at every quiz went, or only at `quiz`.
+fn stats(args: cli::StatsArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let attempts = history::load(&history::history_path().ok_or(NO_DATA_DIR)?)?;
+    let mut stats = history::stats(&attempts);
+    let name = match &args.quiz {
+        Some(quiz) => Some(source_name(quiz).ok_or("Quizzes read from stdin have no history")?),
+        None => None,
+    };
+    if let Some(name) = &name {
+        stats.retain(|x| x.quiz == name);
+    }
+    if stats.is_empty() {
+        match name {
+            Some(name) => println!("{name} has not been taken until the end yet"),
+            None => println!("No quiz was taken until the end yet"),
         }
+        return Ok(ExitCode::SUCCESS);
     }
 
-    let list = get_questions(&instance).unwrap();
+    let now = save::now();
+    let reports: Vec<String> = stats
+        .iter()
+        .map(|x| template::StatsTemplate::new(x, now).render())
+        .collect::<Result<_, _>>()?;
+    println!("{}", reports.join("\n\n"));
 
-    let mut question_list_display = QuestionListDisplay::new(list);
-    let mut answers = HashMap::new();
+    Ok(ExitCode::SUCCESS)
+}
 
-    let mut current_command = Command::Unknown;
-    while current_command != Command::Quit {
-        clearscreen::clear().expect("failed to clear screen");
+/// Writes `quiz` to `path`, unless it does not match the bundled schema.
+async fn write_quiz(
+    path: &std::path::Path,
+    quiz: &serde_json::Value,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let text = serde_json::to_string_pretty(quiz)?;
+    if !check(&text, &path.display().to_string(), None).await? {
+        eprintln!("The quiz was not written to {}", path.display());
+        return Ok(ExitCode::from(cli::EXIT_VALIDATION_ERROR));
+    }
 
-        let question_text = question_list_display.current_question();
-        // Print stuff to screen
-        print!("{question_text}");
+    save::write_json(path, quiz)?;
+    let questions = match quiz["questions"].as_array().map_or(0, Vec::len) {
+        1 => "1 question".to_string(),
+        count => format!("{count} questions"),
+    };
+    println!("\n{questions} written to {}", path.display());
+    Ok(ExitCode::SUCCESS)
+}
 
-        // Needed to make sure that the text right before the user input
-        // gets written to the screen.
-        std::io::stdout().flush().unwrap();
+async fn new(args: cli::NewArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    if args.path.exists() && !args.force {
+        return Err(format!(
+            "{0} already exists, add questions to it with `terminal_quizlit add-question {0}` \
+             or overwrite it with --force",
+            args.path.display()
+        )
+        .into());
+    }
+
+    let quiz = author::Wizard::new(&mut std::io::stdin().lock(), &mut std::io::stdout())
+        .new_quiz()
+        .await?;
+    write_quiz(&args.path, &quiz).await
+}
+
+async fn add_question(args: cli::AddQuestionArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let text = Source::Path(args.path.clone()).read().await?;
+    // Better to find out before typing the questions than when saving them.
+    if !check(&text, &args.path.display().to_string(), None).await? {
+        return Ok(ExitCode::from(cli::EXIT_VALIDATION_ERROR));
+    }
+    let mut quiz = serde_json::from_str(&text)?;
+
+    author::Wizard::new(&mut std::io::stdin().lock(), &mut std::io::stdout())
+        .add_questions(&mut quiz)
+        .await?;
+    write_quiz(&args.path, &quiz).await
+}
 
-        // Get user input
-        let mut user_input = String::new();
-        std::io::stdin().read_line(&mut user_input)?;
+/// Checks every quiz file in `args` and reports what is wrong with them.
+async fn validate(args: cli::ValidateArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let compiled = compile_schema(args.schema.source()).await?;
 
-        current_command = parse_command(user_input.trim());
+    let mut reports = Vec::new();
+    for pattern in &args.files {
+        let paths = match glob::is_pattern(pattern) {
+            true => glob::expand(pattern),
+            false => vec![std::path::PathBuf::from(pattern)],
+        };
+        if paths.is_empty() {
+            let diagnostic = diagnostic::Diagnostic::file("io", "No files match".to_string());
+            reports.push((
+                diagnostic::FileReport::new(pattern.clone(), vec![diagnostic]),
+                String::new(),
+            ));
+        }
 
-        match current_command {
-            Command::Next => match question_list_display.next() {
-                Some(_) => {}
-                None => {
-                    println!("No next question")
+        for path in paths {
+            let (text, mut diagnostics) = match std::fs::read_to_string(&path) {
+                Ok(text) => {
+                    let mut diagnostics = diagnostic::check(&text, &compiled);
+                    if diagnostics.is_empty() {
+                        diagnostics = lint::lint(&text, &args.allow);
+                    }
+                    (text, diagnostics)
                 }
-            },
-            Command::Previous => match question_list_display.previous() {
-                Some(_) => {}
-                None => {
-                    println!("No previous question")
+                Err(error) => {
+                    let message = format!("Unable to read it: {error}");
+                    (
+                        String::new(),
+                        vec![diagnostic::Diagnostic::file("io", message)],
+                    )
                 }
-            },
-            Command::Quit => {}
-            Command::Unknown => {}
-            Command::Answer => {
-                answers.insert(question_list_display.current_index(), user_input.clone());
-                match question_list_display.next() {
-                    Some(_) => {}
-                    None => {
-                        println!("No next question")
-                    }
+            };
+            if args.deny_warnings {
+                for diagnostic in &mut diagnostics {
+                    diagnostic.level = diagnostic::Level::Error;
+                }
+            }
+            let file = path.display().to_string();
+            reports.push((diagnostic::FileReport::new(file, diagnostics), text));
+        }
+    }
+
+    let invalid = reports.iter().filter(|(x, _)| !x.valid).count();
+    match args.format {
+        cli::Format::Text => {
+            for (report, text) in &reports {
+                for diagnostic in &report.diagnostics {
+                    println!("{}\n", diagnostic.render(&report.file, text));
                 }
             }
+            let count = |count: usize, noun: &str| match count {
+                1 => format!("1 {noun}"),
+                count => format!("{count} {noun}s"),
+            };
+            let warnings = reports
+                .iter()
+                .flat_map(|(x, _)| &x.diagnostics)
+                .filter(|x| x.level == diagnostic::Level::Warning)
+                .count();
+            let problems = match (invalid, warnings) {
+                (0, 0) => "no problems found".to_string(),
+                (0, warnings) => count(warnings, "warning"),
+                (invalid, 0) => format!("{invalid} with errors"),
+                (invalid, warnings) => {
+                    format!("{invalid} with errors, {}", count(warnings, "warning"))
+                }
+            };
+            println!("Checked {}, {problems}", count(reports.len(), "file"));
+        }
+        cli::Format::Json => {
+            let reports: Vec<_> = reports.iter().map(|(x, _)| x).collect();
+            println!("{}", serde_json::to_string_pretty(&reports)?);
         }
     }
 
-    println!("{:?}", answers);
+    match invalid {
+        0 => Ok(ExitCode::SUCCESS),
+        _ => Ok(ExitCode::from(cli::EXIT_VALIDATION_ERROR)),
+    }
+}
+
+/// Prints the unfinished sessions in `dir`.
+fn list_sessions(dir: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+    let sessions = save::list(dir)?;
+    if sessions.is_empty() {
+        println!("There are no unfinished sessions");
+        return Ok(());
+    }
+
+    let now = save::now();
+    for (store, saved) in sessions {
+        println!("{}  {}", store.id(), saved.quiz.source);
+        println!(
+            "    {}/{} answered, {} spent, saved {}",
+            saved.progress.answers.len(),
+            saved.layout.questions.len(),
+            template::format_clock(saved.progress.elapsed),
+            save::ago(now.saturating_sub(saved.saved_at))
+        );
+    }
+    println!("\nGo on with one with `terminal_quizlit resume <id>`");
 
     Ok(())
 }
+
+async fn resume(args: cli::ResumeArgs) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let dir = save::sessions_dir().ok_or(NO_DATA_DIR)?;
+    let Some(id) = &args.id else {
+        list_sessions(&dir)?;
+        return Ok(ExitCode::SUCCESS);
+    };
+
+    let (store, saved) = save::find(&dir, id)?;
+    let source = Source::parse(&saved.quiz.source)?;
+    let instance = source.load().await?;
+    let Some(list) = questions(&instance) else {
+        return Ok(ExitCode::from(cli::EXIT_VALIDATION_ERROR));
+    };
+
+    let quiz = save::QuizIdentity::new(saved.quiz.source.clone(), &instance, &list);
+    let unchanged = saved.quiz.check(&quiz)?;
+    let mut session = session::QuizSession::new(
+        list,
+        saved.layout,
+        saved.seed,
+        saved.options,
+        saved.practice,
+    )
+    .with_time_limits(saved.limits)
+    .with_mode(saved.mode)
+    .with_store(store, quiz);
+    session.restore(saved.progress);
+    if !unchanged {
+        session.notify(
+            "The quiz changed since the session was saved, but its questions, options and \
+             correct answers are the same"
+                .to_string(),
+        );
+    }
+    let mut input = None;
+    take(&mut session, args.interface, &mut input, &source).await?;
+    record(&session, saved.quiz.source);
+    retry(session, true, args.interface, &mut input, &source).await?;
+
+    Ok(ExitCode::SUCCESS)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = cli::Cli::parse();
+
+    let result = match cli.command {
+        cli::Commands::Run(args) => run(args).await,
+        cli::Commands::Resume(args) => resume(args).await,
+        cli::Commands::Due => due().await,
+        cli::Commands::Stats(args) => stats(args),
+        cli::Commands::New(args) => new(args).await,
+        cli::Commands::AddQuestion(args) => add_question(args).await,
+        cli::Commands::Validate(args) => validate(args).await,
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            ExitCode::from(cli::EXIT_RUNTIME_ERROR)
+        }
+    }
+}
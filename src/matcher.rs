@@ -0,0 +1,187 @@
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How a free text answer compares to the accepted answers.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TextMatch {
+    /// Equal to an accepted answer once both are normalized.
+    Exact,
+    /// Within the typo tolerance of `answer`.
+    Typo {
+        answer: String,
+    },
+    /// Wrong, but close enough to `answer` to point it out.
+    Close {
+        answer: String,
+    },
+    NoMatch,
+}
+
+/// Normalizes free text before comparing it: Unicode compatibility forms are
+/// folded (NFKC), accents are dropped, punctuation is treated as whitespace and
+/// runs of whitespace collapse into a single space.
+pub fn normalize(text: &str, case_sensitive: bool) -> String {
+    let folded: String = text
+        .nfkc()
+        .collect::<String>()
+        .nfd()
+        .filter(|x| !is_combining_mark(*x))
+        .map(|x| match x.is_alphanumeric() {
+            true => x,
+            false => ' ',
+        })
+        .collect();
+
+    let text = folded.split_whitespace().collect::<Vec<&str>>().join(" ");
+    match case_sensitive {
+        true => text,
+        false => text.to_lowercase(),
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, x) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != *y);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Matches `input` against the `accepted` answers. Up to `tolerance` edits are
+/// forgiven, answers a few edits further away are reported as close.
+pub fn match_text(
+    input: &str,
+    accepted: &[String],
+    case_sensitive: bool,
+    tolerance: usize,
+) -> TextMatch {
+    let input = normalize(input, case_sensitive);
+    if input.is_empty() {
+        return TextMatch::NoMatch;
+    }
+
+    let Some((distance, answer)) = accepted
+        .iter()
+        .map(|x| (edit_distance(&input, &normalize(x, case_sensitive)), x))
+        .min_by_key(|(distance, _)| *distance)
+    else {
+        return TextMatch::NoMatch;
+    };
+
+    // An answer counts as close when at most a third of it is off.
+    let close = tolerance.max(answer.chars().count() / 3).max(1);
+    match distance {
+        0 => TextMatch::Exact,
+        x if x <= tolerance => TextMatch::Typo {
+            answer: answer.trim().to_string(),
+        },
+        x if x <= close => TextMatch::Close {
+            answer: answer.trim().to_string(),
+        },
+        _ => TextMatch::NoMatch,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let cases = vec![
+            (
+                "whitespace",
+                "  Monkey   D\tLuffy ",
+                false,
+                "monkey d luffy",
+            ),
+            ("punctuation", "Monkey D. Luffy!", false, "monkey d luffy"),
+            ("hyphen", "Roronoa-Zoro", false, "roronoa zoro"),
+            ("accents", "Pokémon Café", false, "pokemon cafe"),
+            ("compatibility forms", "Ｌｕｆｆｙ ﬁve", false, "luffy five"),
+            ("case sensitive", "Nico Robin", true, "Nico Robin"),
+        ];
+
+        for (case_name, text, case_sensitive, expected) in cases {
+            assert_eq!(
+                normalize(text, case_sensitive),
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        let cases = vec![
+            ("", "", 0),
+            ("lucy", "", 4),
+            ("lucy", "lucy", 0),
+            ("lucy", "lucky", 1),
+            ("lcuy", "lucy", 2),
+            ("kitten", "sitting", 3),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(edit_distance(a, b), expected, "case: {a} {b} failed");
+        }
+    }
+
+    #[test]
+    fn test_match_text() {
+        let accepted = vec!["Lucy".to_string(), "Monkey D. Luffy".to_string()];
+        let lucy = "Lucy".to_string();
+        let cases = vec![
+            ("exact", "lucy", false, 0, TextMatch::Exact),
+            ("normalized", " monkey d luffy ", false, 0, TextMatch::Exact),
+            (
+                "case sensitive",
+                "lucy",
+                true,
+                0,
+                TextMatch::Close {
+                    answer: lucy.clone(),
+                },
+            ),
+            (
+                "close",
+                "lucky",
+                false,
+                0,
+                TextMatch::Close {
+                    answer: lucy.clone(),
+                },
+            ),
+            (
+                "typo",
+                "lucky",
+                false,
+                1,
+                TextMatch::Typo {
+                    answer: lucy.clone(),
+                },
+            ),
+            ("wrong", "nami", false, 1, TextMatch::NoMatch),
+            ("empty", " ", false, 3, TextMatch::NoMatch),
+        ];
+
+        for (case_name, input, case_sensitive, tolerance, expected) in cases {
+            assert_eq!(
+                match_text(input, &accepted, case_sensitive, tolerance),
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+
+        assert_eq!(match_text("lucy", &[], false, 1), TextMatch::NoMatch);
+    }
+}
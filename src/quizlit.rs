@@ -1,3 +1,12 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{push_token, QuizlitError};
+use crate::matcher::{match_text, TextMatch};
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum QuestionType {
     TrueFalse,
@@ -18,254 +27,783 @@ impl QuestionType {
     }
 }
 
-#[allow(dead_code)]
 pub trait QuestionTrait {
     fn get_type(&self) -> &QuestionType;
-    fn get_question(&self) -> Result<String, String>;
-    fn get_answers(&self) -> Result<Answers, String>;
+    fn get_question(&self) -> &str;
+    fn get_answers(&self) -> &Answers;
 }
 
 impl QuestionTrait for Question {
     fn get_type(&self) -> &QuestionType {
-        &self.question_type
+        self.answers.question_type()
+    }
+
+    fn get_question(&self) -> &str {
+        &self.question
     }
-    fn get_question(&self) -> Result<String, String> {
-        if self.data.get("question").is_none() {
-            return Err("Could not find the question field".to_string());
+
+    fn get_answers(&self) -> &Answers {
+        &self.answers
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum GradeStatus {
+    Correct,
+    Incorrect,
+    Partial,
+}
+
+/// The outcome of grading a single answer.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Grade {
+    pub status: GradeStatus,
+    /// Fraction of the question that was answered correctly, from 0.0 to 1.0.
+    pub score: f32,
+    /// The answer(s) that would have been graded as correct.
+    pub expected: Vec<String>,
+    /// Explanations attached to the correct answer(s).
+    pub explanations: Vec<String>,
+    /// Explanations attached to the incorrect option(s) that were picked.
+    pub incorrect_explanations: Vec<String>,
+    /// Extra feedback, e.g. that a free text answer was close to an accepted one.
+    pub note: Option<String>,
+}
+
+impl Grade {
+    fn new(score: f32, expected: Vec<String>, explanations: Vec<String>) -> Self {
+        let status = if score >= 1.0 {
+            GradeStatus::Correct
+        } else if score > 0.0 {
+            GradeStatus::Partial
+        } else {
+            GradeStatus::Incorrect
+        };
+
+        Self {
+            status,
+            score,
+            expected,
+            explanations,
+            incorrect_explanations: Vec::new(),
+            note: None,
         }
+    }
+}
 
-        Ok(self
-            .data
-            .get("question")
-            .unwrap()
-            .as_str()
-            .unwrap()
-            .to_string())
+/// The label an answer is shown with, "a" for the first answer.
+pub fn choice_label(index: usize) -> String {
+    match index < 26 {
+        true => ((b'a' + index as u8) as char).to_string(),
+        false => (index + 1).to_string(),
+    }
+}
+
+/// Turns a single choice, a letter like "c" or a 1-based number like "3",
+/// into an index into `answers()`.
+fn parse_choice(token: &str) -> Option<usize> {
+    let mut chars = token.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c @ 'a'..='z'), None) => Some(c as usize - 'a' as usize),
+        (Some(c @ 'A'..='Z'), None) => Some(c as usize - 'A' as usize),
+        _ => token
+            .parse::<usize>()
+            .ok()
+            .filter(|x| *x > 0)
+            .map(|x| x - 1),
     }
+}
+
+fn split_choices(input: &str) -> impl Iterator<Item = &str> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+}
 
-    fn get_answers(&self) -> Result<Answers, String> {
-        if self.data.get("answers").is_none() {
-            return Err("Could not find the answers field".to_string());
+/// Turns choices like "a c", "a,c" or "1 3" into indexes into `answers()`.
+fn parse_choices(input: &str) -> Option<Vec<usize>> {
+    split_choices(input).map(parse_choice).collect()
+}
+
+/// Parses distinct choices out of `count` options, naming them `noun` in errors.
+fn parse_distinct_choices(input: &str, count: usize, noun: &str) -> Result<Vec<usize>, String> {
+    let mut choices = Vec::new();
+
+    for token in split_choices(input) {
+        let Some(index) = parse_choice(token) else {
+            return Err(format!("\"{token}\" is not a letter or a number"));
+        };
+        if index >= count {
+            return Err(format!("\"{token}\" is not one of the {noun}"));
+        }
+        if choices.contains(&index) {
+            return Err(format!("\"{token}\" is given more than once"));
         }
+        choices.push(index);
+    }
 
-        let data = self.data.get("answers").unwrap().clone();
-        let question_type = self.get_type().clone();
+    Ok(choices)
+}
 
-        Ok(Answers {
-            data,
-            question_type,
-        })
+/// Parses the picks of a selection question with `count` options, e.g. "a c" or "1,3".
+pub fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    let selected = parse_distinct_choices(input, count, "options")?;
+    match selected.is_empty() {
+        true => Err("Pick at least one option".to_string()),
+        false => Ok(selected),
+    }
+}
+
+/// Parses an ordering of `count` items such as "c a d b" or "3,1,4,2".
+pub fn parse_permutation(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    let order = parse_distinct_choices(input, count, "items")?;
+
+    let missing: Vec<String> = (0..count)
+        .filter(|x| !order.contains(x))
+        .map(choice_label)
+        .collect();
+    match missing.is_empty() {
+        true => Ok(order),
+        false => Err(format!("Missing {}", missing.join(", "))),
+    }
+}
+
+/// How selection questions with several correct answers are scored.
+#[derive(Debug, PartialEq, Clone, Copy, Default, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scoring {
+    /// Full credit only when exactly the correct options are picked
+    AllOrNothing,
+    /// Correct picks divided by the number of correct answers or of picks, whichever is larger
+    #[default]
+    Proportional,
+    /// Correct picks minus wrong picks, divided by the number of correct answers
+    Penalty,
+}
+
+/// Settings that apply to grading every question of a quiz.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct GradeOptions {
+    pub scoring: Scoring,
+    /// Edits forgiven in free text answers, unless the question sets its own tolerance.
+    pub typo_tolerance: usize,
+}
+
+impl Scoring {
+    /// Scores `hits` correct and `misses` wrong picks out of `correct` correct answers.
+    fn score(&self, hits: usize, misses: usize, correct: usize) -> f32 {
+        match self {
+            Self::AllOrNothing => match hits == correct && misses == 0 {
+                true => 1.0,
+                false => 0.0,
+            },
+            Self::Proportional => hits as f32 / correct.max(hits + misses) as f32,
+            Self::Penalty => (hits as f32 - misses as f32).max(0.0) / correct as f32,
+        }
     }
 }
 
-#[allow(dead_code)]
 pub trait AnswerTrait {
-    //TODO: add randomized_answers method and implement it in the trait
     fn answers(&self) -> Option<Vec<String>>;
     fn correct_answers(&self) -> Option<Vec<(String, Option<String>)>>;
-    fn validate_answer(&self, input: String) -> bool;
+    fn validate_answer(&self, input: &str, options: GradeOptions) -> Grade;
+
+    /// Explains why an input can not be graded, e.g. an order with a missing item.
+    fn check_input(&self, _input: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// The answers in a random order, each paired with its index in `answers()`.
+    fn randomized_answers<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Vec<(usize, String)>> {
+        let mut answers: Vec<(usize, String)> = self.answers()?.into_iter().enumerate().collect();
+        answers.shuffle(rng);
+        Some(answers)
+    }
+
+    /// Describes a user's input in terms of the answers it refers to,
+    /// e.g. "a" becomes "a. Gum Gum Fruit".
+    fn display_answer(&self, input: &str) -> String {
+        let (Some(answers), Some(indexes)) = (self.answers(), parse_choices(input)) else {
+            return input.trim().to_string();
+        };
+
+        if indexes.is_empty() {
+            return input.trim().to_string();
+        }
+
+        indexes
+            .iter()
+            .map(|index| {
+                let letter = choice_label(*index);
+                match answers.get(*index) {
+                    Some(answer) => format!("{letter}. {answer}"),
+                    None => format!("{letter}. ?"),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+}
+
+/// A single answer option, with an optional explanation of why it is (in)correct.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct AnswerOption {
+    pub answer: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct TrueFalseAnswers {
+    pub correct: AnswerOption,
+    pub incorrect: AnswerOption,
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
-pub struct Answers {
-    data: serde_json::Value,
-    question_type: QuestionType,
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct SelectionAnswers {
+    pub correct: Vec<AnswerOption>,
+    pub incorrect: Vec<AnswerOption>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct UserInputAnswers {
+    pub accepted: Vec<String>,
+    #[serde(rename = "caseSensitive", default)]
+    pub case_sensitive: bool,
+    /// Edits forgiven when matching, overrides the quiz wide tolerance.
+    #[serde(
+        rename = "typoTolerance",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub typo_tolerance: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct OrderAnswers {
+    /// The items in their correct order.
+    pub correct: Vec<AnswerOption>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Answers {
+    TrueFalse(TrueFalseAnswers),
+    Selection(SelectionAnswers),
+    UserInput(UserInputAnswers),
+    Order(OrderAnswers),
+}
+
+impl Answers {
+    pub fn question_type(&self) -> &QuestionType {
+        match self {
+            Self::TrueFalse(_) => &QuestionType::TrueFalse,
+            Self::Selection(_) => &QuestionType::Selection,
+            Self::UserInput(_) => &QuestionType::UserInput,
+            Self::Order(_) => &QuestionType::Order,
+        }
+    }
+}
+
+fn answer_texts<'a>(options: impl IntoIterator<Item = &'a AnswerOption>) -> Vec<String> {
+    options.into_iter().map(|x| x.answer.clone()).collect()
+}
+
+fn answers_with_explanations(options: &[AnswerOption]) -> Vec<(String, Option<String>)> {
+    options
+        .iter()
+        .map(|x| (x.answer.clone(), x.explanation.clone()))
+        .collect()
+}
+
+impl TrueFalseAnswers {
+    /// The option `input` picks by its text: 0 for the correct option and 1
+    /// for the incorrect one. The options are shown without labels, so
+    /// letters and numbers do not pick them.
+    fn choice(&self, input: &str) -> Option<usize> {
+        let input = input.trim();
+        [&self.correct, &self.incorrect]
+            .iter()
+            .position(|x| x.answer.eq_ignore_ascii_case(input))
+    }
+}
+
+impl Answers {
+    /// Explanations of the incorrect options picked by `input`, e.g. why
+    /// "False" is not the answer to a true or false question.
+    fn incorrect_explanations(&self, input: &str) -> Vec<String> {
+        let picked: Vec<&AnswerOption> = match self {
+            Self::TrueFalse(answers) => match answers.choice(input) {
+                Some(1) => vec![&answers.incorrect],
+                _ => vec![],
+            },
+            Self::Selection(answers) => {
+                let count = answers.correct.len() + answers.incorrect.len();
+                parse_selection(input, count)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|x| x.checked_sub(answers.correct.len()))
+                    .map(|x| &answers.incorrect[x])
+                    .collect()
+            }
+            Self::UserInput(_) | Self::Order(_) => vec![],
+        };
+
+        picked
+            .into_iter()
+            .filter_map(|x| x.explanation.clone())
+            .collect()
+    }
 }
 
 impl AnswerTrait for Answers {
-    #[allow(clippy::vec_init_then_push)]
     fn answers(&self) -> Option<Vec<String>> {
-        match self.question_type.clone() {
-            QuestionType::TrueFalse => {
-                let mut results = Vec::new();
-                results.push(
-                    self.data
-                        .get("correct")
-                        .expect("correct")
-                        .get("answer")
-                        .expect("answer")
-                        .as_str()
-                        .expect("string")
-                        .to_string(),
-                );
-                results.push(
-                    self.data
-                        .get("incorrect")
-                        .expect("correct")
-                        .get("answer")
-                        .expect("answer")
-                        .as_str()
-                        .expect("string")
-                        .to_string(),
-                );
-
-                Some(results)
-            }
-            QuestionType::Order => {
-                let mut results = Vec::new();
-                let answers = self
-                    .data
-                    .get("correct")
-                    .expect("correct answers")
-                    .as_array()
-                    .expect("valid json")
-                    .clone();
-
-                for answer in answers {
-                    results.push(
-                        answer
-                            .get("answer")
-                            .expect("answer string")
-                            .as_str()
-                            .expect("a string")
-                            .to_string(),
-                    );
+        match self {
+            Self::TrueFalse(answers) => Some(answer_texts([&answers.correct, &answers.incorrect])),
+            Self::Order(answers) => Some(answer_texts(&answers.correct)),
+            Self::UserInput(_) => None,
+            Self::Selection(answers) => Some(answer_texts(
+                answers.correct.iter().chain(answers.incorrect.iter()),
+            )),
+        }
+    }
+
+    fn correct_answers(&self) -> Option<Vec<(String, Option<String>)>> {
+        match self {
+            Self::TrueFalse(answers) => Some(answers_with_explanations(std::slice::from_ref(
+                &answers.correct,
+            ))),
+            Self::Order(answers) => Some(answers_with_explanations(&answers.correct)),
+            // The explanation goes with the first accepted answer, the others
+            // are usually spellings of the same one.
+            Self::UserInput(answers) => Some(
+                answers
+                    .accepted
+                    .iter()
+                    .enumerate()
+                    .map(|(index, x)| {
+                        (
+                            x.clone(),
+                            answers.explanation.clone().filter(|_| index == 0),
+                        )
+                    })
+                    .collect(),
+            ),
+            Self::Selection(answers) => Some(answers_with_explanations(&answers.correct)),
+        }
+    }
+
+    fn validate_answer(&self, input: &str, options: GradeOptions) -> Grade {
+        let input = input.trim();
+
+        let correct = self.correct_answers().unwrap_or_default();
+        let explanations = correct.iter().filter_map(|(_, x)| x.clone()).collect();
+        let expected: Vec<String> = correct.into_iter().map(|(x, _)| x).collect();
+
+        let score = match self {
+            Self::TrueFalse(answers) => match answers.choice(input) {
+                Some(0) => 1.0,
+                _ => 0.0,
+            },
+            Self::UserInput(answers) => return grade_text(answers, input, options),
+            Self::Selection(answers) => {
+                let count = answers.correct.len() + answers.incorrect.len();
+                match parse_selection(input, count) {
+                    Ok(selected) => {
+                        let hits = selected.iter().filter(|x| **x < expected.len()).count();
+                        options
+                            .scoring
+                            .score(hits, selected.len() - hits, expected.len())
+                    }
+                    Err(_) => 0.0,
                 }
+            }
+            Self::Order(_) => {
+                let order = parse_permutation(input, expected.len()).unwrap_or_default();
+                let in_place = order
+                    .iter()
+                    .enumerate()
+                    .filter(|(position, index)| position == *index)
+                    .count();
 
-                Some(results)
+                in_place as f32 / expected.len() as f32
             }
-            QuestionType::UserInput => None,
-            QuestionType::Selection => {
-                let mut results = Vec::new();
-                let correct_answers = self
-                    .data
-                    .get("correct")
-                    .expect("correct answers")
-                    .as_array()
-                    .expect("valid json")
-                    .clone();
-
-                let incorrect_answers = self
-                    .data
-                    .get("incorrect")
-                    .expect("incorrect answers")
-                    .as_array()
-                    .expect("valid json")
-                    .clone();
-
-                for answer in correct_answers.iter().chain(incorrect_answers.iter()) {
-                    results.push(
-                        answer
-                            .get("answer")
-                            .expect("answer string")
-                            .as_str()
-                            .expect("a string")
-                            .to_string(),
-                    );
-                }
+        };
 
-                Some(results)
+        Grade {
+            incorrect_explanations: self.incorrect_explanations(input),
+            ..Grade::new(score, expected, explanations)
+        }
+    }
+
+    fn check_input(&self, input: &str) -> Result<(), String> {
+        match self {
+            Self::TrueFalse(answers) => match answers.choice(input) {
+                Some(_) => Ok(()),
+                None => Err(format!("\"{}\" is not one of the options", input.trim())),
+            },
+            Self::Order(answers) => parse_permutation(input, answers.correct.len()).map(|_| ()),
+            Self::Selection(answers) => {
+                let count = answers.correct.len() + answers.incorrect.len();
+                parse_selection(input, count).map(|_| ())
             }
+            _ => Ok(()),
         }
     }
-    #[allow(clippy::vec_init_then_push)]
-    fn correct_answers(&self) -> Option<Vec<(String, Option<String>)>> {
-        match self.question_type.clone() {
-            QuestionType::TrueFalse => {
-                let mut results = vec![];
-                results.push((
-                    self.data
-                        .get("correct")
-                        .expect("correct")
-                        .get("answer")
-                        .expect("answer")
-                        .as_str()
-                        .expect("string")
-                        .to_string(),
-                    self.data
-                        .get("correct")
-                        .expect("correct")
-                        .get("explanation")
-                        .map(|x| x.as_str().unwrap().to_string()),
-                ));
-
-                Some(results)
+}
+
+fn grade_text(answers: &UserInputAnswers, input: &str, options: GradeOptions) -> Grade {
+    let tolerance = answers.typo_tolerance.unwrap_or(options.typo_tolerance);
+    let (score, note) =
+        match match_text(input, &answers.accepted, answers.case_sensitive, tolerance) {
+            TextMatch::Exact => (1.0, None),
+            TextMatch::Typo { answer } => {
+                (1.0, Some(format!("close enough, it is spelled {answer}")))
             }
-            QuestionType::Order => {
-                let mut results = vec![];
-                let answers = self
-                    .data
-                    .get("correct")
-                    .expect("correct answers")
-                    .as_array()
-                    .expect("valid json")
-                    .clone();
-
-                for answer in answers {
-                    results.push((
-                        answer
-                            .get("answer")
-                            .expect("answer string")
-                            .as_str()
-                            .expect("a string")
-                            .to_string(),
-                        answer
-                            .get("explanation")
-                            .map(|x| x.as_str().unwrap().to_string()),
-                    ));
-                }
+            TextMatch::Close { answer } => {
+                (0.0, Some(format!("close \u{2014} did you mean {answer}?")))
+            }
+            TextMatch::NoMatch => (0.0, None),
+        };
+
+    Grade {
+        note,
+        ..Grade::new(
+            score,
+            answers.accepted.clone(),
+            answers.explanation.clone().into_iter().collect(),
+        )
+    }
+}
+
+/// Answers shown in a shuffled order. Letters typed by the user refer to the
+/// shuffled order and are mapped back to the original answers before grading.
+pub struct ShuffledAnswers<'a> {
+    answers: &'a Answers,
+    /// `order[position]` is the index into `answers.answers()` shown at `position`.
+    order: Vec<usize>,
+}
+
+impl<'a> ShuffledAnswers<'a> {
+    pub fn new(answers: &'a Answers, order: Vec<usize>) -> Self {
+        Self { answers, order }
+    }
+
+    /// Rewrites choices referring to the shuffled order into letters
+    /// referring to the original order, anything else is left untouched.
+    fn original_input(&self, input: &str) -> String {
+        match parse_choices(input) {
+            Some(indexes) if !indexes.is_empty() && !self.order.is_empty() => indexes
+                .iter()
+                .map(|x| choice_label(*self.order.get(*x).unwrap_or(x)))
+                .collect::<Vec<String>>()
+                .join(" "),
+            _ => input.to_string(),
+        }
+    }
 
-                Some(results)
+    /// An answer as it is shown, e.g. "c. Gum Gum Fruit", `original` indexes `answers()`.
+    fn shown_answer(&self, original: usize) -> String {
+        let position = self
+            .order
+            .iter()
+            .position(|x| *x == original)
+            .unwrap_or(original);
+        let answers = self.answers.answers().unwrap_or_default();
+        format!("{}. {}", choice_label(position), answers[original])
+    }
+
+    /// A nudge towards the correct answer that does not give all of it away.
+    pub fn hint(&self) -> String {
+        match self.answers {
+            Answers::Selection(answers) if answers.correct.len() > 1 => {
+                format!("{} of the options are correct", answers.correct.len())
             }
-            QuestionType::UserInput => None,
-            QuestionType::Selection => {
-                let mut results = Vec::new();
-                let correct_answers = self
-                    .data
-                    .get("correct")
-                    .expect("correct answers")
-                    .as_array()
-                    .expect("valid json")
-                    .clone();
-
-                for answer in correct_answers.iter() {
-                    results.push((
-                        answer
-                            .get("answer")
-                            .expect("answer string")
-                            .as_str()
-                            .expect("a string")
-                            .to_string(),
-                        answer
-                            .get("explanation")
-                            .map(|x| x.as_str().unwrap().to_string()),
-                    ));
+            Answers::Selection(answers) if !answers.incorrect.is_empty() => format!(
+                "It is not {}",
+                self.shown_answer(answers.correct.len() + answers.incorrect.len() - 1)
+            ),
+            Answers::Order(answers) if answers.correct.len() > 1 => {
+                format!("{} comes first", self.shown_answer(0))
+            }
+            Answers::UserInput(answers) => match answers.accepted.first().map(|x| x.trim()) {
+                Some(answer) if !answer.is_empty() => format!(
+                    "It starts with \"{}\" and is {} characters long",
+                    answer.chars().next().unwrap_or_default(),
+                    answer.chars().count()
+                ),
+                _ => "There is no hint for this question".to_string(),
+            },
+            _ => "There is no hint for this question".to_string(),
+        }
+    }
+}
+
+impl AnswerTrait for ShuffledAnswers<'_> {
+    fn answers(&self) -> Option<Vec<String>> {
+        let answers = self.answers.answers()?;
+        Some(self.order.iter().map(|x| answers[*x].clone()).collect())
+    }
+
+    fn correct_answers(&self) -> Option<Vec<(String, Option<String>)>> {
+        self.answers.correct_answers()
+    }
+
+    fn validate_answer(&self, input: &str, options: GradeOptions) -> Grade {
+        self.answers
+            .validate_answer(&self.original_input(input), options)
+    }
+
+    fn check_input(&self, input: &str) -> Result<(), String> {
+        // Whether an input can be graded does not depend on the answer order.
+        self.answers.check_input(input)
+    }
+}
+
+/// The order questions, and the answers of each question, are shown in.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Layout {
+    /// Question indexes in the order they are asked.
+    pub questions: Vec<usize>,
+    /// The answer order of every question, see `ShuffledAnswers`.
+    pub answers: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    /// Builds a layout that is always the same for the same `seed`. Order
+    /// questions are always scrambled.
+    pub fn new(
+        questions: &[Question],
+        seed: u64,
+        shuffle_answers: bool,
+        shuffle_questions: bool,
+    ) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+        let mut question_order: Vec<usize> = (0..questions.len()).collect();
+        if shuffle_questions {
+            question_order.shuffle(&mut rng);
+        }
+
+        let answers = questions
+            .iter()
+            .map(|question| {
+                let answers = question.get_answers();
+                let count = answers.answers().map_or(0, |x| x.len());
+                let mut shuffle = || -> Vec<usize> {
+                    answers
+                        .randomized_answers(&mut rng)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|(index, _)| index)
+                        .collect()
+                };
+
+                match (shuffle_answers, answers) {
+                    // Otherwise the correct option would always be shown first.
+                    (true, Answers::Selection(_) | Answers::TrueFalse(_)) => shuffle(),
+                    // Showing order items in their correct order gives the answer away.
+                    (_, Answers::Order(_)) if count > 1 => loop {
+                        let order = shuffle();
+                        if order.iter().enumerate().any(|(i, x)| i != *x) {
+                            break order;
+                        }
+                    },
+                    _ => (0..count).collect(),
                 }
+            })
+            .collect();
 
-                Some(results)
-            }
+        Self {
+            questions: question_order,
+            answers,
         }
     }
-    fn validate_answer(&self, _input: String) -> bool {
-        todo!();
+}
+
+/// The on-disk shape of a question, the `kind` field picks the answers shape.
+#[derive(Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum QuestionData {
+    TrueFalse {
+        question: String,
+        answers: TrueFalseAnswers,
+    },
+    Selection {
+        question: String,
+        answers: SelectionAnswers,
+    },
+    UserInput {
+        question: String,
+        answers: UserInputAnswers,
+    },
+    Order {
+        question: String,
+        answers: OrderAnswers,
+    },
+}
+
+impl From<QuestionData> for Question {
+    fn from(data: QuestionData) -> Self {
+        let (question, answers) = match data {
+            QuestionData::TrueFalse { question, answers } => {
+                (question, Answers::TrueFalse(answers))
+            }
+            QuestionData::Selection { question, answers } => {
+                (question, Answers::Selection(answers))
+            }
+            QuestionData::UserInput { question, answers } => {
+                (question, Answers::UserInput(answers))
+            }
+            QuestionData::Order { question, answers } => (question, Answers::Order(answers)),
+        };
+
+        Self { question, answers }
+    }
+}
+
+impl From<Question> for QuestionData {
+    fn from(data: Question) -> Self {
+        let question = data.question;
+        match data.answers {
+            Answers::TrueFalse(answers) => Self::TrueFalse { question, answers },
+            Answers::Selection(answers) => Self::Selection { question, answers },
+            Answers::UserInput(answers) => Self::UserInput { question, answers },
+            Answers::Order(answers) => Self::Order { question, answers },
+        }
     }
 }
 
-#[derive(Debug)]
-#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(from = "QuestionData", into = "QuestionData")]
 pub struct Question {
-    data: serde_json::Value,
-    question_type: QuestionType,
+    question: String,
+    answers: Answers,
+}
+
+/// Deserializes `data`, which lives at `pointer` in the quiz document.
+fn parse_value<T: DeserializeOwned>(
+    data: &serde_json::Value,
+    pointer: &str,
+) -> Result<T, QuizlitError> {
+    serde_path_to_error::deserialize(data).map_err(|e| QuizlitError::from_serde(pointer, e))
+}
+
+/// Looks up a required field of the object at `pointer`.
+fn required_field<'a>(
+    data: &'a serde_json::Value,
+    pointer: &str,
+    name: &str,
+) -> Result<(&'a serde_json::Value, String), QuizlitError> {
+    let mut field_pointer = pointer.to_string();
+    push_token(&mut field_pointer, name);
+
+    match data.get(name) {
+        Some(value) => Ok((value, field_pointer)),
+        None => Err(QuizlitError::MissingField {
+            pointer: field_pointer,
+        }),
+    }
+}
+
+fn not_empty<T>(list: &[T], pointer: &str, name: &str) -> Result<(), QuizlitError> {
+    match list.is_empty() {
+        true => {
+            let mut pointer = pointer.to_string();
+            push_token(&mut pointer, name);
+            Err(QuizlitError::EmptyAnswers { pointer })
+        }
+        false => Ok(()),
+    }
 }
 
 impl Question {
-    pub fn new(data: serde_json::Value) -> Result<Self, String> {
-        if data.get("kind").is_none() {
-            return Err("Json data does not have a \"kind\" field".to_string());
+    #[allow(dead_code)]
+    pub fn new(data: serde_json::Value) -> Result<Self, QuizlitError> {
+        Self::parse(&data, "")
+    }
+
+    /// Parses the question that lives at `pointer` in the quiz document.
+    pub fn parse(data: &serde_json::Value, pointer: &str) -> Result<Self, QuizlitError> {
+        if !data.is_object() {
+            return Err(QuizlitError::WrongType {
+                pointer: pointer.to_string(),
+                expected: "an object".to_string(),
+            });
         }
 
-        let kind = data
-            .get("kind")
-            .expect("kind field should exist")
-            .as_str()
-            .unwrap();
+        let (kind, kind_pointer) = required_field(data, pointer, "kind")?;
+        let kind: String = parse_value(kind, &kind_pointer)?;
+        let question_type =
+            QuestionType::from_str(&kind).map_err(|_| QuizlitError::UnknownKind {
+                pointer: kind_pointer,
+                kind,
+            })?;
 
-        let question_type = QuestionType::from_str(kind).expect("valid question type");
+        let (question, question_pointer) = required_field(data, pointer, "question")?;
+        let question: String = parse_value(question, &question_pointer)?;
+
+        let (answers, answers_pointer) = required_field(data, pointer, "answers")?;
+        let answers = match question_type {
+            QuestionType::TrueFalse => Answers::TrueFalse(parse_value(answers, &answers_pointer)?),
+            QuestionType::Selection => {
+                let answers: SelectionAnswers = parse_value(answers, &answers_pointer)?;
+                not_empty(&answers.correct, &answers_pointer, "correct")?;
+                Answers::Selection(answers)
+            }
+            QuestionType::UserInput => Answers::UserInput(parse_value(answers, &answers_pointer)?),
+            QuestionType::Order => {
+                let answers: OrderAnswers = parse_value(answers, &answers_pointer)?;
+                not_empty(&answers.correct, &answers_pointer, "correct")?;
+                Answers::Order(answers)
+            }
+        };
+
+        Ok(Self { question, answers })
+    }
+}
+
+/// A whole quiz document.
+#[derive(Debug, PartialEq, Clone, Deserialize, Serialize)]
+pub struct Quiz {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub questions: Vec<Question>,
+}
+
+impl Quiz {
+    /// Parses a quiz, checking each question separately so errors can point
+    /// at the malformed question.
+    pub fn new(data: &serde_json::Value) -> Result<Self, QuizlitError> {
+        let (json_questions, questions_pointer) = required_field(data, "", "questions")?;
+        let json_questions: &Vec<serde_json::Value> =
+            json_questions
+                .as_array()
+                .ok_or_else(|| QuizlitError::WrongType {
+                    pointer: questions_pointer.clone(),
+                    expected: "an array".to_string(),
+                })?;
+
+        let mut questions = Vec::new();
+        for (index, question) in json_questions.iter().enumerate() {
+            questions.push(Question::parse(
+                question,
+                &format!("{questions_pointer}/{index}"),
+            )?);
+        }
+
+        let text = |field: &str| match data.get(field) {
+            Some(value) => parse_value(value, &format!("/{field}")),
+            None => Ok(None),
+        };
 
         Ok(Self {
-            data,
-            question_type,
+            title: text("title")?,
+            description: text("description")?,
+            questions,
         })
     }
 }
@@ -376,7 +914,23 @@ mod tests {
             (
                 "user_input",
                 Question::new(get_user_input_question_json()).unwrap(),
-                None,
+                Some(vec![("Lucy".to_string(), None)]),
+            ),
+            (
+                "user_input with explanation",
+                Question::new(serde_json::json!({
+                    "kind": "user_input",
+                    "question": "Who is the captain of the Straw Hat Pirates?",
+                    "answers": {
+                        "accepted": ["Luffy", "Monkey D. Luffy"],
+                        "explanation": "He founded the crew"
+                    }
+                }))
+                .unwrap(),
+                Some(vec![
+                    ("Luffy".to_string(), Some("He founded the crew".to_string())),
+                    ("Monkey D. Luffy".to_string(), None),
+                ]),
             ),
             (
                 "selection",
@@ -397,7 +951,7 @@ mod tests {
 
         for (case_name, question, expected) in cases {
             assert_eq!(
-                question.get_answers().unwrap().correct_answers(),
+                question.get_answers().correct_answers(),
                 expected,
                 "case: {case_name} failed"
             );
@@ -441,28 +995,750 @@ mod tests {
 
         for (case_name, question, expected) in cases {
             assert_eq!(
-                question.get_answers().unwrap().answers(),
+                question.get_answers().answers(),
                 expected,
                 "case: {case_name} failed"
             );
         }
     }
 
+    #[test]
+    fn test_question_validate_answer() {
+        let cases = vec![
+            (
+                "true_false correct",
+                get_true_false_question_json(),
+                "true",
+                GradeStatus::Correct,
+            ),
+            (
+                "true_false incorrect",
+                get_true_false_question_json(),
+                "False",
+                GradeStatus::Incorrect,
+            ),
+            (
+                "true_false label",
+                get_true_false_question_json(),
+                "a",
+                GradeStatus::Incorrect,
+            ),
+            (
+                "user_input case insensitive",
+                get_user_input_question_json(),
+                "lucy",
+                GradeStatus::Correct,
+            ),
+            (
+                "user_input incorrect",
+                get_user_input_question_json(),
+                "Luffy",
+                GradeStatus::Incorrect,
+            ),
+            (
+                "selection correct",
+                get_selection_question_json(),
+                "a",
+                GradeStatus::Correct,
+            ),
+            (
+                "selection incorrect",
+                get_selection_question_json(),
+                "c",
+                GradeStatus::Incorrect,
+            ),
+            (
+                "selection garbage",
+                get_selection_question_json(),
+                "gum gum",
+                GradeStatus::Incorrect,
+            ),
+            (
+                "order correct",
+                get_order_question_json(),
+                "a b c d",
+                GradeStatus::Correct,
+            ),
+            (
+                "order partially correct",
+                get_order_question_json(),
+                "a,b,d,c",
+                GradeStatus::Partial,
+            ),
+            (
+                "order incorrect",
+                get_order_question_json(),
+                "d c b a",
+                GradeStatus::Incorrect,
+            ),
+        ];
+
+        for (case_name, json, input, expected) in cases {
+            let question = Question::new(json).unwrap();
+            let grade = question
+                .get_answers()
+                .validate_answer(input, GradeOptions::default());
+            assert_eq!(grade.status, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_question_validate_answer_expected() {
+        let question = Question::new(get_true_false_question_json()).unwrap();
+        let grade = question
+            .get_answers()
+            .validate_answer("False", GradeOptions::default());
+
+        assert_eq!(grade.score, 0.0);
+        assert_eq!(grade.expected, vec!["True".to_string()]);
+        assert_eq!(grade.explanations, vec!["Because 7 8 (ate) 9".to_string()]);
+
+        let question = Question::new(get_user_input_question_json()).unwrap();
+        let grade = question
+            .get_answers()
+            .validate_answer("Lucy", GradeOptions::default());
+
+        assert_eq!(grade.score, 1.0);
+        assert_eq!(grade.expected, vec!["Lucy".to_string()]);
+        assert!(grade.explanations.is_empty());
+    }
+
+    #[test]
+    fn test_incorrect_explanations() {
+        let mut selection = get_selection_question_json();
+        selection["answers"]["incorrect"][1]["explanation"] =
+            serde_json::json!("There is no such fruit");
+
+        let because = vec!["Because 7 8 (ate) 9".to_string()];
+        let no_such_fruit = vec!["There is no such fruit".to_string()];
+        let cases = vec![
+            (
+                "true_false incorrect",
+                get_true_false_question_json(),
+                "false",
+                because,
+            ),
+            (
+                "true_false correct",
+                get_true_false_question_json(),
+                "True",
+                vec![],
+            ),
+            (
+                "selection incorrect",
+                selection.clone(),
+                "c",
+                no_such_fruit.clone(),
+            ),
+            (
+                "selection several",
+                selection.clone(),
+                "a b c",
+                no_such_fruit,
+            ),
+            ("selection no explanation", selection.clone(), "b", vec![]),
+            ("selection invalid", selection, "z", vec![]),
+            ("user_input", get_user_input_question_json(), "Nami", vec![]),
+        ];
+
+        for (case_name, json, input, expected) in cases {
+            let question = Question::new(json).unwrap();
+            let grade = question
+                .get_answers()
+                .validate_answer(input, GradeOptions::default());
+            assert_eq!(
+                grade.incorrect_explanations, expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_user_input_typo_tolerance() {
+        let mut json = get_user_input_question_json();
+        let question = Question::new(json.clone()).unwrap();
+        json["answers"]["typoTolerance"] = serde_json::json!(2);
+        let tolerant_question = Question::new(json).unwrap();
+
+        let close = Some("close \u{2014} did you mean Lucy?".to_string());
+        let spelled = Some("close enough, it is spelled Lucy".to_string());
+        let cases = vec![
+            ("exact", &question, " lucy. ", 0, GradeStatus::Correct, None),
+            (
+                "near miss",
+                &question,
+                "Lucky",
+                0,
+                GradeStatus::Incorrect,
+                close,
+            ),
+            (
+                "quiz tolerance",
+                &question,
+                "Lucky",
+                1,
+                GradeStatus::Correct,
+                spelled.clone(),
+            ),
+            (
+                "question tolerance",
+                &tolerant_question,
+                "Lcuy",
+                0,
+                GradeStatus::Correct,
+                spelled,
+            ),
+            (
+                "wrong",
+                &tolerant_question,
+                "Nami",
+                0,
+                GradeStatus::Incorrect,
+                None,
+            ),
+        ];
+
+        for (case_name, question, input, typo_tolerance, status, note) in cases {
+            let options = GradeOptions {
+                typo_tolerance,
+                ..Default::default()
+            };
+            let grade = question.get_answers().validate_answer(input, options);
+            assert_eq!(grade.status, status, "case: {case_name} failed");
+            assert_eq!(grade.note, note, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_question_display_answer() {
+        let cases = vec![
+            ("true_false", get_true_false_question_json(), "True", "True"),
+            (
+                "user_input",
+                get_user_input_question_json(),
+                " Lucy ",
+                "Lucy",
+            ),
+            (
+                "selection",
+                get_selection_question_json(),
+                "b",
+                "b. Stretch Stretch Fruit",
+            ),
+            (
+                "selection out of range",
+                get_selection_question_json(),
+                "a z",
+                "a. Gum Gum Fruit, z. ?",
+            ),
+            (
+                "order",
+                get_order_question_json(),
+                "b a",
+                "b. Impel Down Arc, a. Amazon Lily Arc",
+            ),
+        ];
+
+        for (case_name, json, input, expected) in cases {
+            let question = Question::new(json).unwrap();
+            assert_eq!(
+                question.get_answers().display_answer(input),
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_shuffled_answers() {
+        let question = Question::new(get_selection_question_json()).unwrap();
+        let shuffled = ShuffledAnswers::new(question.get_answers(), vec![2, 0, 3, 1]);
+
+        assert_eq!(
+            shuffled.answers().unwrap(),
+            vec![
+                "Ruber Ruber Fruit",
+                "Gum Gum Fruit",
+                "Hungry Hungry Fruit",
+                "Stretch Stretch Fruit",
+            ]
+        );
+
+        let cases = vec![
+            ("b", GradeStatus::Correct, "b. Gum Gum Fruit"),
+            ("a", GradeStatus::Incorrect, "a. Ruber Ruber Fruit"),
+            ("z", GradeStatus::Incorrect, "z. ?"),
+        ];
+        for (input, expected, display) in cases {
+            assert_eq!(
+                shuffled
+                    .validate_answer(input, GradeOptions::default())
+                    .status,
+                expected,
+                "case: {input} failed"
+            );
+            assert_eq!(
+                shuffled.display_answer(input),
+                display,
+                "case: {input} failed"
+            );
+        }
+
+        let question = Question::new(get_user_input_question_json()).unwrap();
+        let shuffled = ShuffledAnswers::new(question.get_answers(), vec![]);
+        assert_eq!(shuffled.answers(), None);
+        assert_eq!(shuffled.original_input("a"), "a");
+    }
+
+    #[test]
+    fn test_layout() {
+        let questions: Vec<Question> = (0..10)
+            .map(|_| Question::new(get_selection_question_json()).unwrap())
+            .collect();
+
+        let layout = Layout::new(&questions, 42, true, true);
+        assert_eq!(layout, Layout::new(&questions, 42, true, true));
+        assert_ne!(layout, Layout::new(&questions, 43, true, true));
+
+        let mut sorted = layout.questions.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<usize>>());
+        for order in &layout.answers {
+            let mut sorted = order.clone();
+            sorted.sort();
+            assert_eq!(sorted, vec![0, 1, 2, 3]);
+        }
+
+        let layout = Layout::new(&questions, 42, false, false);
+        assert_eq!(layout.questions, (0..10).collect::<Vec<usize>>());
+        assert!(layout.answers.iter().all(|x| x == &vec![0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn test_shuffled_answers_hint() {
+        let mut selection = get_selection_question_json();
+        let single = Question::new(selection.clone()).unwrap();
+        selection["answers"]["correct"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({"answer": "Gomu Gomu no Mi"}));
+        let several = Question::new(selection).unwrap();
+        let order = Question::new(get_order_question_json()).unwrap();
+        let user_input = Question::new(get_user_input_question_json()).unwrap();
+        let true_false = Question::new(get_true_false_question_json()).unwrap();
+
+        let cases = vec![
+            (
+                "selection",
+                &single,
+                vec![3, 0, 2, 1],
+                "It is not a. Hungry Hungry Fruit",
+            ),
+            (
+                "select all",
+                &several,
+                vec![0, 1, 2, 3, 4],
+                "2 of the options are correct",
+            ),
+            (
+                "order",
+                &order,
+                vec![2, 0, 1, 3],
+                "b. Amazon Lily Arc comes first",
+            ),
+            (
+                "user_input",
+                &user_input,
+                vec![],
+                "It starts with \"L\" and is 4 characters long",
+            ),
+            (
+                "true_false",
+                &true_false,
+                vec![0, 1],
+                "There is no hint for this question",
+            ),
+        ];
+
+        for (case_name, question, order, expected) in cases {
+            let shuffled = ShuffledAnswers::new(question.get_answers(), order);
+            assert_eq!(shuffled.hint(), expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_layout_answer_orders() {
+        let questions = vec![
+            Question::new(get_true_false_question_json()).unwrap(),
+            Question::new(get_user_input_question_json()).unwrap(),
+            Question::new(get_order_question_json()).unwrap(),
+        ];
+
+        let mut true_false_orders = Vec::new();
+        for (seed, shuffle_answers) in (0..10).zip([true, false].into_iter().cycle()) {
+            let layout = Layout::new(&questions, seed, shuffle_answers, false);
+            let mut true_false = layout.answers[0].clone();
+            if shuffle_answers {
+                true_false_orders.push(true_false.clone());
+            } else {
+                assert_eq!(true_false, vec![0, 1], "case: {seed} failed");
+            }
+            true_false.sort();
+            assert_eq!(true_false, vec![0, 1], "case: {seed} failed");
+            assert_eq!(
+                layout.answers[1],
+                Vec::<usize>::new(),
+                "case: {seed} failed"
+            );
+
+            let mut order = layout.answers[2].clone();
+            assert_ne!(order, vec![0, 1, 2, 3], "case: {seed} failed");
+            order.sort();
+            assert_eq!(order, vec![0, 1, 2, 3], "case: {seed} failed");
+        }
+
+        // The correct option is not always shown first.
+        assert!(true_false_orders.contains(&vec![0, 1]));
+        assert!(true_false_orders.contains(&vec![1, 0]));
+
+        let shuffled = ShuffledAnswers::new(questions[0].get_answers(), vec![1, 0]);
+        assert_eq!(
+            shuffled.answers(),
+            Some(vec!["False".to_string(), "True".to_string()])
+        );
+        let grade = shuffled.validate_answer("True", GradeOptions::default());
+        assert_eq!(grade.status, GradeStatus::Correct);
+    }
+
+    #[test]
+    fn test_parse_permutation() {
+        let cases = vec![
+            ("c a d b", Ok(vec![2, 0, 3, 1])),
+            ("3,1,4,2", Ok(vec![2, 0, 3, 1])),
+            ("C, a 4 2", Ok(vec![2, 0, 3, 1])),
+            ("c a d", Err("Missing b".to_string())),
+            ("", Err("Missing a, b, c, d".to_string())),
+            (
+                "c a a d b",
+                Err("\"a\" is given more than once".to_string()),
+            ),
+            (
+                "c a e d b",
+                Err("\"e\" is not one of the items".to_string()),
+            ),
+            (
+                "c a 0 d",
+                Err("\"0\" is not a letter or a number".to_string()),
+            ),
+            (
+                "cadb",
+                Err("\"cadb\" is not a letter or a number".to_string()),
+            ),
+        ];
+
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_permutation(input, 4),
+                expected,
+                "case: '{input}' failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_shuffled_order_answers() {
+        let question = Question::new(get_order_question_json()).unwrap();
+        let shuffled = ShuffledAnswers::new(question.get_answers(), vec![2, 0, 3, 1]);
+
+        let cases = vec![
+            ("b d a c", GradeStatus::Correct),
+            ("2,4,1,3", GradeStatus::Correct),
+            ("b d c a", GradeStatus::Partial),
+            ("a b c d", GradeStatus::Incorrect),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                shuffled
+                    .validate_answer(input, GradeOptions::default())
+                    .status,
+                expected,
+                "case: {input} failed"
+            );
+        }
+
+        assert_eq!(shuffled.check_input("b d a c"), Ok(()));
+        assert_eq!(shuffled.check_input("b d a"), Err("Missing c".to_string()));
+        assert_eq!(
+            shuffled.display_answer("b d"),
+            "b. Amazon Lily Arc, d. Impel Down Arc"
+        );
+    }
+
+    #[test]
+    fn test_scoring() {
+        let cases = vec![
+            (Scoring::AllOrNothing, 2, 0, 1.0),
+            (Scoring::AllOrNothing, 1, 0, 0.0),
+            (Scoring::AllOrNothing, 2, 1, 0.0),
+            (Scoring::Proportional, 2, 0, 1.0),
+            (Scoring::Proportional, 1, 0, 0.5),
+            (Scoring::Proportional, 1, 1, 0.5),
+            (Scoring::Proportional, 2, 2, 0.5),
+            (Scoring::Penalty, 2, 0, 1.0),
+            (Scoring::Penalty, 2, 1, 0.5),
+            (Scoring::Penalty, 1, 1, 0.0),
+            (Scoring::Penalty, 0, 2, 0.0),
+        ];
+
+        for (scoring, hits, misses, expected) in cases {
+            assert_eq!(
+                scoring.score(hits, misses, 2),
+                expected,
+                "case: {scoring:?} with {hits} hits and {misses} misses failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_answer_selection() {
+        let json = serde_json::json!({
+            "kind": "selection",
+            "question": "Who are members of the Straw Hat crew?",
+            "answers": {
+                "correct": [{"answer": "Zoro"}, {"answer": "Nami"}],
+                "incorrect": [{"answer": "Buggy"}, {"answer": "Crocodile"}]
+            }
+        });
+        let question = Question::new(json).unwrap();
+        let answers = question.get_answers();
+
+        let cases = vec![
+            ("a,b", Scoring::AllOrNothing, GradeStatus::Correct),
+            ("b a", Scoring::AllOrNothing, GradeStatus::Correct),
+            ("a", Scoring::AllOrNothing, GradeStatus::Incorrect),
+            ("a", Scoring::Proportional, GradeStatus::Partial),
+            ("a c", Scoring::Proportional, GradeStatus::Partial),
+            ("a c", Scoring::Penalty, GradeStatus::Incorrect),
+            ("a b c", Scoring::Penalty, GradeStatus::Partial),
+            ("c d", Scoring::Proportional, GradeStatus::Incorrect),
+        ];
+        for (input, scoring, expected) in cases {
+            assert_eq!(
+                answers
+                    .validate_answer(
+                        input,
+                        GradeOptions {
+                            scoring,
+                            ..Default::default()
+                        }
+                    )
+                    .status,
+                expected,
+                "case: '{input}' with {scoring:?} failed"
+            );
+        }
+
+        let cases = vec![
+            ("a c", Ok(())),
+            ("", Err("Pick at least one option".to_string())),
+            ("a e", Err("\"e\" is not one of the options".to_string())),
+            ("a 1", Err("\"1\" is given more than once".to_string())),
+            (
+                "a x1",
+                Err("\"x1\" is not a letter or a number".to_string()),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                answers.check_input(input),
+                expected,
+                "case: '{input}' failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_true_false_check_input() {
+        let question = Question::new(get_true_false_question_json()).unwrap();
+        let answers = question.get_answers();
+
+        let cases = vec![
+            ("True", Ok(())),
+            (" false ", Ok(())),
+            ("a", Err("\"a\" is not one of the options".to_string())),
+            ("2", Err("\"2\" is not one of the options".to_string())),
+            (
+                "ture",
+                Err("\"ture\" is not one of the options".to_string()),
+            ),
+            ("c", Err("\"c\" is not one of the options".to_string())),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                answers.check_input(input),
+                expected,
+                "case: '{input}' failed"
+            );
+        }
+    }
+
     #[test]
     fn test_question_happy_path() {
         let question = Question::new(get_true_false_question_json()).unwrap();
 
         assert_eq!(question.get_type(), &QuestionType::TrueFalse);
         assert_eq!(
-            question.get_question().unwrap(),
-            "Is the number 9 is afraid of the number 7?".to_string()
+            question.get_question(),
+            "Is the number 9 is afraid of the number 7?"
         );
-        assert!(question.get_answers().is_ok());
 
-        let answers_list = question.get_answers().unwrap().answers().unwrap();
+        let answers_list = question.get_answers().answers().unwrap();
         assert_eq!(answers_list, vec!["True", "False"]);
     }
 
+    #[test]
+    fn test_question_new_errors() {
+        let cases = vec![
+            (
+                "not an object",
+                serde_json::json!("question?"),
+                QuizlitError::WrongType {
+                    pointer: "".to_string(),
+                    expected: "an object".to_string(),
+                },
+            ),
+            (
+                "missing kind",
+                serde_json::json!({"question": "question?"}),
+                QuizlitError::MissingField {
+                    pointer: "/kind".to_string(),
+                },
+            ),
+            (
+                "kind is not a string",
+                serde_json::json!({"kind": 1, "question": "question?"}),
+                QuizlitError::WrongType {
+                    pointer: "/kind".to_string(),
+                    expected: "a string".to_string(),
+                },
+            ),
+            (
+                "unknown kind",
+                serde_json::json!({"kind": "essay", "question": "question?"}),
+                QuizlitError::UnknownKind {
+                    pointer: "/kind".to_string(),
+                    kind: "essay".to_string(),
+                },
+            ),
+            (
+                "missing answers",
+                serde_json::json!({"kind": "order", "question": "question?"}),
+                QuizlitError::MissingField {
+                    pointer: "/answers".to_string(),
+                },
+            ),
+            (
+                "missing nested field",
+                serde_json::json!({
+                    "kind": "true_false",
+                    "question": "question?",
+                    "answers": {"correct": {"answer": "True"}}
+                }),
+                QuizlitError::MissingField {
+                    pointer: "/answers/incorrect".to_string(),
+                },
+            ),
+            (
+                "answer is not a string",
+                serde_json::json!({
+                    "kind": "order",
+                    "question": "question?",
+                    "answers": {"correct": [{"answer": "a"}, {"answer": 1}]}
+                }),
+                QuizlitError::WrongType {
+                    pointer: "/answers/correct/1/answer".to_string(),
+                    expected: "a string".to_string(),
+                },
+            ),
+            (
+                "empty answers",
+                serde_json::json!({
+                    "kind": "selection",
+                    "question": "question?",
+                    "answers": {"correct": [], "incorrect": [{"answer": "a"}]}
+                }),
+                QuizlitError::EmptyAnswers {
+                    pointer: "/answers/correct".to_string(),
+                },
+            ),
+        ];
+
+        for (case_name, json, expected) in cases {
+            assert_eq!(
+                Question::new(json).unwrap_err(),
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_question_typed_answers() {
+        let question = Question::new(get_user_input_question_json()).unwrap();
+        let expected = Answers::UserInput(UserInputAnswers {
+            accepted: vec!["Lucy".to_string()],
+            case_sensitive: false,
+            typo_tolerance: None,
+            explanation: None,
+        });
+
+        assert_eq!(question.get_answers(), &expected);
+        assert_eq!(question.get_type(), &QuestionType::UserInput);
+    }
+
+    #[test]
+    fn test_question_serialize_round_trip() {
+        for json in [
+            get_true_false_question_json(),
+            get_selection_question_json(),
+            get_user_input_question_json(),
+            get_order_question_json(),
+        ] {
+            let question = Question::new(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&question).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn test_quiz_new() {
+        let json = serde_json::json!({
+            "title": "One Piece",
+            "questions": [get_true_false_question_json(), get_order_question_json()]
+        });
+        let quiz = Quiz::new(&json).unwrap();
+
+        assert_eq!(quiz.title, Some("One Piece".to_string()));
+        assert_eq!(quiz.description, None);
+        assert_eq!(quiz.questions.len(), 2);
+
+        let json = serde_json::json!({
+            "questions": [get_true_false_question_json(), {"kind": "order"}]
+        });
+        assert_eq!(
+            Quiz::new(&json).unwrap_err().to_string(),
+            "/questions/1/question: missing required field"
+        );
+
+        let json = serde_json::json!({"questions": {}});
+        assert_eq!(
+            Quiz::new(&json).unwrap_err(),
+            QuizlitError::WrongType {
+                pointer: "/questions".to_string(),
+                expected: "an array".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn test_question_type_from_str() {
         let cases = vec![
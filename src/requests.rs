@@ -5,3 +5,7 @@
 pub async fn get_json(url: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
     Ok(reqwest::get(url).await?.json::<serde_json::Value>().await?)
 }
+
+pub async fn get_text(url: &str) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(reqwest::get(url).await?.text().await?)
+}
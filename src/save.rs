@@ -0,0 +1,431 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::quizlit::{AnswerTrait, GradeOptions, Layout, Question, QuestionTrait};
+use crate::session::{Mode, Progress, TimeLimits};
+
+/// The quiz a session was taken on, checked again before the session is resumed.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct QuizIdentity {
+    /// Where the quiz was loaded from, an absolute path or a URL.
+    pub source: String,
+    /// Hash of the whole quiz document.
+    pub hash: String,
+    /// Hash of the questions and options of the quiz, see `fingerprint`.
+    pub fingerprint: String,
+}
+
+impl QuizIdentity {
+    pub fn new(source: String, quiz: &serde_json::Value, questions: &[Question]) -> Self {
+        Self {
+            source,
+            hash: format!("{:016x}", fnv1a(quiz.to_string().as_bytes())),
+            fingerprint: format!("{:016x}", fnv1a(fingerprint(questions).as_bytes())),
+        }
+    }
+
+    /// Whether a session saved on `self` can go on with the quiz as it is now,
+    /// `Ok(false)` means the quiz changed but the answers still fit.
+    pub fn check(&self, now: &QuizIdentity) -> Result<bool, String> {
+        if self.fingerprint != now.fingerprint {
+            return Err(format!(
+                "The quiz {} changed since the session was saved, its questions, \
+                 options or correct answers are not the same anymore",
+                self.source
+            ));
+        }
+
+        Ok(self.hash == now.hash)
+    }
+}
+
+/// Describes the questions of a quiz by their kind, text, options in order and
+/// correct answers. Saved answers refer to questions and options by index,
+/// they only make sense as long as this stays the same.
+pub fn fingerprint(questions: &[Question]) -> String {
+    let questions: Vec<serde_json::Value> = questions
+        .iter()
+        .map(|x| {
+            let answers = x.get_answers();
+            let correct: Option<Vec<String>> = answers
+                .correct_answers()
+                .map(|x| x.into_iter().map(|(answer, _)| answer).collect());
+            serde_json::json!([
+                format!("{:?}", x.get_type()),
+                x.get_question(),
+                answers.answers(),
+                correct,
+            ])
+        })
+        .collect();
+
+    serde_json::Value::Array(questions).to_string()
+}
+
+/// 64 bit FNV-1a, used because the hashers of the standard library may change
+/// between Rust releases and saved sessions have to outlive those.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Everything written to the file of a saved session.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub quiz: QuizIdentity,
+    pub seed: u64,
+    pub layout: Layout,
+    pub options: GradeOptions,
+    pub practice: bool,
+    #[serde(default)]
+    pub mode: Mode,
+    pub progress: Progress,
+    #[serde(default)]
+    pub limits: TimeLimits,
+    /// Seconds since the Unix epoch.
+    pub saved_at: u64,
+}
+
+/// The file a session is saved to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Store {
+    path: PathBuf,
+}
+
+impl Store {
+    /// The file for the session with `seed` on `quiz`, so the same quiz can be
+    /// taken with different layouts at the same time.
+    pub fn new(dir: &Path, quiz: &QuizIdentity, seed: u64) -> Self {
+        Self {
+            path: dir.join(format!("{}-{seed}.json", quiz.hash)),
+        }
+    }
+
+    /// How the session is named when resuming it.
+    pub fn id(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn write(&self, session: &SavedSession) -> std::io::Result<()> {
+        write_json(&self.path, session)
+    }
+
+    /// Deletes the saved session, once it is over there is nothing to resume.
+    pub fn remove(&self) -> std::io::Result<()> {
+        match std::fs::remove_file(&self.path) {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => Err(error),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes `value` to a temporary file first, so a crash while saving does not
+/// lose what was saved before.
+pub fn write_json(path: &Path, value: &impl Serialize) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let temporary = path.with_extension("json.tmp");
+    std::fs::write(&temporary, serde_json::to_string_pretty(value)?)?;
+    std::fs::rename(&temporary, path)
+}
+
+/// Where sessions are saved.
+pub fn sessions_dir() -> Option<PathBuf> {
+    data_dir().map(|x| x.join("sessions"))
+}
+
+/// Where everything kept between runs lives, under the XDG data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let home = std::env::var_os("APPDATA");
+    #[cfg(not(windows))]
+    let home = std::env::var_os("HOME").map(|x| Path::new(&x).join(".local").join("share").into());
+
+    xdg_data_home(std::env::var_os("XDG_DATA_HOME"), home).map(|x| x.join("terminal_quizlit"))
+}
+
+/// `XDG_DATA_HOME`, which has to be absolute to count, or else `fallback`.
+fn xdg_data_home(xdg_data_home: Option<OsString>, fallback: Option<OsString>) -> Option<PathBuf> {
+    xdg_data_home
+        .map(PathBuf::from)
+        .filter(|x| x.is_absolute())
+        .or_else(|| fallback.map(PathBuf::from))
+}
+
+/// Every session saved in `dir`, the most recently saved first. Files that
+/// cannot be read are left out.
+pub fn list(dir: &Path) -> std::io::Result<Vec<(Store, SavedSession)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut sessions = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|x| x != "json") {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(session) = serde_json::from_str::<SavedSession>(&text) {
+            sessions.push((Store { path }, session));
+        }
+    }
+
+    sessions.sort_by_key(|(_, session)| std::cmp::Reverse(session.saved_at));
+    Ok(sessions)
+}
+
+/// The saved session whose id is or starts with `id`.
+pub fn find(dir: &Path, id: &str) -> Result<(Store, SavedSession), Box<dyn std::error::Error>> {
+    let mut matches: Vec<(Store, SavedSession)> = list(dir)?
+        .into_iter()
+        .filter(|(store, _)| store.id().starts_with(id))
+        .collect();
+
+    match matches.len() {
+        0 => Err(format!("There is no saved session {id}").into()),
+        1 => Ok(matches.remove(0)),
+        _ => Err(format!("{id} matches several saved sessions, use more of the id").into()),
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |x| x.as_secs())
+}
+
+/// How long ago something happened, e.g. "3 hours ago".
+pub fn ago(seconds: u64) -> String {
+    let (count, unit) = match seconds {
+        0..=59 => return "just now".to_string(),
+        60..=3599 => (seconds / 60, "minute"),
+        3600..=86399 => (seconds / 3600, "hour"),
+        _ => (seconds / 86400, "day"),
+    };
+
+    match count {
+        1 => format!("1 {unit} ago"),
+        _ => format!("{count} {unit}s ago"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::quizlit::Quiz;
+    use serde_json::json;
+
+    fn quiz() -> serde_json::Value {
+        json!({
+            "questions": [
+                {
+                    "kind": "true_false",
+                    "question": "Is the number 9 is afraid of the number 7?",
+                    "answers": {
+                        "correct": {"answer": "True"},
+                        "incorrect": {"answer": "False"}
+                    }
+                },
+                {
+                    "kind": "selection",
+                    "question": "What devil fruit did Luffy eat?",
+                    "answers": {
+                        "correct": [{"answer": "Gum Gum Fruit"}],
+                        "incorrect": [{"answer": "Buggy Buggy Fruit"}]
+                    }
+                }
+            ]
+        })
+    }
+
+    fn identity(quiz: &serde_json::Value) -> QuizIdentity {
+        let questions = Quiz::new(quiz).unwrap().questions;
+        QuizIdentity::new("quiz.json".to_string(), quiz, &questions)
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let questions = Quiz::new(&quiz()).unwrap().questions;
+        let fingerprint: serde_json::Value =
+            serde_json::from_str(&fingerprint(&questions)).unwrap();
+        assert_eq!(
+            fingerprint,
+            json!([
+                [
+                    "TrueFalse",
+                    "Is the number 9 is afraid of the number 7?",
+                    ["True", "False"],
+                    ["True"]
+                ],
+                [
+                    "Selection",
+                    "What devil fruit did Luffy eat?",
+                    ["Gum Gum Fruit", "Buggy Buggy Fruit"],
+                    ["Gum Gum Fruit"]
+                ]
+            ])
+        );
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn test_quiz_identity_check() {
+        let mut explained = quiz();
+        explained["questions"][0]["answers"]["correct"]["explanation"] = json!("7 8 9");
+        let mut reworded = quiz();
+        reworded["questions"][1]["question"] = json!("Which devil fruit did Luffy eat?");
+        let mut extra_option = quiz();
+        extra_option["questions"][1]["answers"]["incorrect"]
+            .as_array_mut()
+            .unwrap()
+            .push(json!({"answer": "Flame Flame Fruit"}));
+        let mut renamed_option = quiz();
+        renamed_option["questions"][1]["answers"]["incorrect"][0]["answer"] =
+            json!("Flame Flame Fruit");
+        // The options are shown in the same order, only which is correct changed.
+        let mut correct_changed = quiz();
+        correct_changed["questions"][1]["answers"] = json!({
+            "correct": [{"answer": "Gum Gum Fruit"}, {"answer": "Buggy Buggy Fruit"}],
+            "incorrect": []
+        });
+        let mut swapped = quiz();
+        swapped["questions"][0]["answers"] = json!({
+            "correct": {"answer": "False"},
+            "incorrect": {"answer": "True"}
+        });
+        let mut fewer_questions = quiz();
+        fewer_questions["questions"].as_array_mut().unwrap().pop();
+
+        let cases = vec![
+            ("unchanged", quiz(), Ok(true)),
+            ("explained", explained, Ok(false)),
+            ("reworded", reworded, Err(())),
+            ("extra option", extra_option, Err(())),
+            ("renamed option", renamed_option, Err(())),
+            ("correct answer changed", correct_changed, Err(())),
+            ("true and false swapped", swapped, Err(())),
+            ("fewer questions", fewer_questions, Err(())),
+        ];
+
+        let saved = identity(&quiz());
+        for (case_name, now, expected) in cases {
+            let result = saved.check(&identity(&now)).map_err(|_| ());
+            assert_eq!(result, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_xdg_data_home() {
+        let cases = vec![
+            (
+                "xdg",
+                Some("/data"),
+                Some("/home/.local/share"),
+                Some("/data"),
+            ),
+            (
+                "fallback",
+                None,
+                Some("/home/.local/share"),
+                Some("/home/.local/share"),
+            ),
+            (
+                "relative xdg",
+                Some("data"),
+                Some("/home/.local/share"),
+                Some("/home/.local/share"),
+            ),
+            ("nothing", None, None, None),
+        ];
+
+        for (case_name, xdg, fallback, expected) in cases {
+            assert_eq!(
+                xdg_data_home(xdg.map(OsString::from), fallback.map(OsString::from)),
+                expected.map(PathBuf::from),
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_store() {
+        let dir = std::env::temp_dir().join(format!("terminal_quizlit-{}", std::process::id()));
+        let quiz = quiz();
+        let questions = Quiz::new(&quiz).unwrap().questions;
+        let saved = SavedSession {
+            quiz: identity(&quiz),
+            seed: 7,
+            layout: Layout::new(&questions, 7, true, false),
+            options: GradeOptions::default(),
+            practice: false,
+            mode: Mode::Flashcards,
+            progress: Progress {
+                position: 1,
+                answers: [(0, "True".to_string())].into(),
+                flagged: [1].into(),
+                elapsed: 65,
+                spent: [(0, 1500)].into(),
+            },
+            limits: TimeLimits {
+                quiz: Some(std::time::Duration::from_secs(600)),
+                question: None,
+            },
+            saved_at: 100,
+        };
+
+        let store = Store::new(&dir, &saved.quiz, 7);
+        assert!(store.id().ends_with("-7"));
+        assert!(!store.exists());
+        store.write(&saved).unwrap();
+        assert!(store.exists());
+
+        let (found, session) = find(&dir, &store.id()[..4]).unwrap();
+        assert_eq!(found, store);
+        assert_eq!(session, saved);
+        assert_eq!(list(&dir).unwrap().len(), 1);
+        assert!(find(&dir, "nope").is_err());
+
+        store.remove().unwrap();
+        store.remove().unwrap();
+        assert!(list(&dir).unwrap().is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(list(&dir).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ago() {
+        let cases = vec![
+            (0, "just now"),
+            (59, "just now"),
+            (60, "1 minute ago"),
+            (3599, "59 minutes ago"),
+            (7200, "2 hours ago"),
+            (86400, "1 day ago"),
+            (864000, "10 days ago"),
+        ];
+
+        for (seconds, expected) in cases {
+            assert_eq!(ago(seconds), expected, "case: {seconds} failed");
+        }
+    }
+}
@@ -1,4 +1,155 @@
-use jsonschema::{Draft, JSONSchema, ValidationError};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use jsonschema::{Draft, JSONSchema, SchemaResolver, SchemaResolverError, ValidationError};
+use url::Url;
+
+use crate::requests;
+
+pub const V1_SCHEMA_ID: &str =
+    "https://raw.githubusercontent.com/Quizlit/schemas/main/src/schemas/v1/quizlit.json";
+
+/// Properties this program reads on top of the v1 schema, kept apart so the
+/// bundled copies of the upstream schemas stay as they are.
+pub const EXTENSIONS_SCHEMA_ID: &str = "urn:terminal_quizlit:schemas:extensions:v1";
+
+/// The v1 schema, every schema it references and the extensions. They are
+/// keyed by their `$id`.
+const BUNDLED_SCHEMAS: [&str; 7] = [
+    include_str!("../schemas/v1/quizlit.json"),
+    include_str!("../schemas/v1/answer.json"),
+    include_str!("../schemas/v1/questions/order.json"),
+    include_str!("../schemas/v1/questions/selection.json"),
+    include_str!("../schemas/v1/questions/true_false.json"),
+    include_str!("../schemas/v1/questions/user_input.json"),
+    include_str!("../schemas/extensions/v1.json"),
+];
+
+/// Resolves `$ref`s from schemas held in memory (or local files), so
+/// compiling a schema never touches the network.
+#[derive(Debug, Clone)]
+pub struct OfflineResolver {
+    documents: HashMap<String, Arc<serde_json::Value>>,
+}
+
+impl OfflineResolver {
+    /// A resolver that knows about the bundled v1 schemas.
+    pub fn new() -> Self {
+        let mut resolver = Self {
+            documents: HashMap::new(),
+        };
+
+        for text in BUNDLED_SCHEMAS {
+            let document: serde_json::Value =
+                serde_json::from_str(text).expect("bundled schemas are valid json");
+            let id = document["$id"]
+                .as_str()
+                .expect("bundled schemas have an $id")
+                .to_string();
+            resolver.add(&id, document);
+        }
+
+        resolver
+    }
+
+    pub fn add(&mut self, id: &str, document: serde_json::Value) {
+        self.documents.insert(id.to_string(), Arc::new(document));
+    }
+
+    /// Downloads every http(s) schema referenced by `schema` that the
+    /// resolver does not know yet, following references recursively.
+    /// This is the only place where schemas are fetched.
+    pub async fn fetch_references(
+        &mut self,
+        schema: &serde_json::Value,
+        base: &Url,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut pending = external_references(schema, base);
+
+        while let Some(url) = pending.pop() {
+            let unknown = !self.documents.contains_key(url.as_str());
+            if unknown && (url.scheme() == "http" || url.scheme() == "https") {
+                let document = requests::get_json(url.as_str()).await?;
+                pending.extend(external_references(&document, &url));
+                self.add(url.as_str(), document);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SchemaResolver for OfflineResolver {
+    fn resolve(
+        &self,
+        _root_schema: &serde_json::Value,
+        url: &Url,
+        _original_reference: &str,
+    ) -> Result<Arc<serde_json::Value>, SchemaResolverError> {
+        let mut url = url.clone();
+        url.set_fragment(None);
+
+        if let Some(document) = self.documents.get(url.as_str()) {
+            return Ok(document.clone());
+        }
+
+        if url.scheme() == "file" {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow::anyhow!("\"{url}\" is not a valid file path"))?;
+            let document = serde_json::from_reader(std::fs::File::open(path)?)?;
+            return Ok(Arc::new(document));
+        }
+
+        Err(anyhow::anyhow!(
+            "\"{url}\" is not bundled and will not be downloaded"
+        ))
+    }
+}
+
+/// Lists the absolute URLs of the documents referenced by `schema`.
+fn external_references(schema: &serde_json::Value, base: &Url) -> Vec<Url> {
+    let mut results = Vec::new();
+
+    match schema {
+        serde_json::Value::Object(object) => {
+            let base = match object.get("$id").and_then(|x| x.as_str()) {
+                Some(id) => base.join(id).unwrap_or_else(|_| base.clone()),
+                None => base.clone(),
+            };
+
+            if let Some(reference) = object.get("$ref").and_then(|x| x.as_str()) {
+                if !reference.starts_with('#') {
+                    if let Ok(mut url) = base.join(reference) {
+                        url.set_fragment(None);
+                        results.push(url);
+                    }
+                }
+            }
+
+            for value in object.values() {
+                results.extend(external_references(value, &base));
+            }
+        }
+        serde_json::Value::Array(array) => {
+            for value in array {
+                results.extend(external_references(value, base));
+            }
+        }
+        _ => {}
+    }
+
+    results
+}
+
+/// The v1 quizlit schema that ships with the binary, along with the
+/// extensions.
+pub fn bundled_schema() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "allOf": [{"$ref": V1_SCHEMA_ID}, {"$ref": EXTENSIONS_SCHEMA_ID}]
+    })
+}
 
 pub fn validate_json<'a>(
     compiled_schema: &'a JSONSchema,
@@ -8,9 +159,19 @@ pub fn validate_json<'a>(
     Ok(())
 }
 
-pub fn compile_json_schema(schema: &serde_json::Value) -> Result<JSONSchema, ValidationError> {
+#[allow(clippy::result_large_err)]
+pub fn compile_json_schema(schema: &serde_json::Value) -> Result<JSONSchema, ValidationError<'_>> {
+    compile_json_schema_with(schema, OfflineResolver::new())
+}
+
+#[allow(clippy::result_large_err)]
+pub fn compile_json_schema_with(
+    schema: &serde_json::Value,
+    resolver: OfflineResolver,
+) -> Result<JSONSchema, ValidationError<'_>> {
     JSONSchema::options()
         .with_draft(Draft::Draft202012)
+        .with_resolver(resolver)
         .compile(schema)
 }
 
@@ -65,4 +226,135 @@ mod tests {
             assert!(result.is_ok(), "case {count}: {name} failed");
         }
     }
+
+    #[test]
+    fn test_bundled_schema() {
+        let compiled = compile_json_schema(&bundled_schema()).expect("A valid schema");
+
+        let cases = vec![
+            (
+                "valid quiz",
+                json!({"questions": [
+                    {
+                        "kind": "true_false",
+                        "question": "question?",
+                        "answers": {
+                            "correct": {"answer": "True", "explanation": "because"},
+                            "incorrect": {"answer": "False"}
+                        }
+                    },
+                    {
+                        "kind": "user_input",
+                        "question": "question?",
+                        "answers": {"accepted": ["Lucy"], "caseSensitive": false}
+                    }
+                ]}),
+                vec![],
+            ),
+            (
+                "unknown kind",
+                json!({"questions": [{"kind": "essay", "question": "question?"}]}),
+                vec!["/questions/0/kind"],
+            ),
+            (
+                "typo tolerance",
+                json!({"questions": [{
+                    "kind": "user_input",
+                    "question": "question?",
+                    "answers": {"accepted": ["Lucy"], "typoTolerance": 2}
+                }]}),
+                vec![],
+            ),
+            (
+                "negative typo tolerance",
+                json!({"questions": [{
+                    "kind": "user_input",
+                    "question": "question?",
+                    "answers": {"accepted": ["Lucy"], "typoTolerance": -1}
+                }]}),
+                vec!["/questions/0/answers/typoTolerance"],
+            ),
+            (
+                "typo tolerance on another kind",
+                json!({"questions": [{
+                    "kind": "order",
+                    "question": "question?",
+                    "answers": {"correct": [{"answer": "a"}], "typoTolerance": -1}
+                }]}),
+                vec![],
+            ),
+            (
+                "referenced answer schema",
+                json!({"questions": [{
+                    "kind": "order",
+                    "question": "question?",
+                    "answers": {"correct": [{"answer": "a"}, {"explanation": "b"}]}
+                }]}),
+                vec!["/questions/0/answers/correct/1"],
+            ),
+        ];
+
+        for (name, instance, expected) in cases {
+            let paths: Vec<String> = match validate_json(&compiled, &instance) {
+                Ok(()) => vec![],
+                Err(errors) => errors.map(|x| x.instance_path.to_string()).collect(),
+            };
+
+            assert_eq!(paths, expected, "case: {name} failed");
+        }
+    }
+
+    #[test]
+    fn test_offline_resolver_rejects_unknown_schemas() {
+        let schema = json!({"$ref": "https://example.com/not-bundled.json"});
+        let compiled = compile_json_schema(&schema).expect("References resolve lazily");
+        let errors: Vec<String> = match validate_json(&compiled, &json!("long foo")) {
+            Ok(()) => vec![],
+            Err(errors) => errors.map(|x| x.to_string()).collect(),
+        };
+        assert_eq!(
+            errors,
+            vec![
+                "failed to resolve https://example.com/not-bundled.json: \
+                \"https://example.com/not-bundled.json\" is not bundled and will not be downloaded"
+            ]
+        );
+
+        let mut resolver = OfflineResolver::new();
+        resolver.add(
+            "https://example.com/not-bundled.json",
+            json!({"maxLength": 5}),
+        );
+        let compiled = compile_json_schema_with(&schema, resolver).expect("A valid schema");
+        assert!(validate_json(&compiled, &json!("long foo")).is_err());
+    }
+
+    #[test]
+    fn test_external_references() {
+        let schema = json!({
+            "$id": "https://example.com/schemas/root.json",
+            "properties": {
+                "a": {"$ref": "a.json"},
+                "b": {"$ref": "#/$defs/b"},
+                "c": {"$ref": "nested/c.json#/$defs/c"},
+                "d": {"$id": "https://other.com/d/", "items": {"$ref": "d.json"}}
+            }
+        });
+
+        let mut references: Vec<String> =
+            external_references(&schema, &Url::parse("file:///").unwrap())
+                .into_iter()
+                .map(|x| x.to_string())
+                .collect();
+        references.sort();
+
+        assert_eq!(
+            references,
+            vec![
+                "https://example.com/schemas/a.json",
+                "https://example.com/schemas/nested/c.json",
+                "https://other.com/d/d.json",
+            ]
+        );
+    }
 }
@@ -0,0 +1,1899 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::BufRead;
+use std::time::{Duration, Instant};
+
+use askama::Template;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::command::{self, Command, Filter, PREFIX};
+use crate::cursor::Cursor;
+use crate::flashcard::{self, SelfGrade};
+use crate::history;
+use crate::quizlit::{self, AnswerTrait, QuestionTrait, QuestionType};
+use crate::save;
+use crate::study;
+use crate::template;
+
+/*
+Thoughts on the display:
+
+I think I can build the display content from wrapped double ended iterators.
+The inner iterator is used for question and answer text and display count (they know the total and index).
+The next layer of iterator handles the user input interface layer (commands and accepting answers).
+
+Anyway, this will make everything disconnected, testable in isolation and
+the main function will only have to deal with a single iterator.
+*/
+
+struct QuestionListDisplay {
+    questions: Vec<quizlit::Question>,
+    layout: quizlit::Layout,
+    cursor: Cursor,
+}
+
+impl QuestionListDisplay {
+    fn new(questions: Vec<quizlit::Question>, layout: quizlit::Layout) -> Self {
+        let cursor = Cursor::new(layout.questions.clone());
+        Self {
+            questions,
+            layout,
+            cursor,
+        }
+    }
+
+    /// The answers of a question in the order they are shown.
+    fn answers(&self, question_index: usize) -> quizlit::ShuffledAnswers<'_> {
+        quizlit::ShuffledAnswers::new(
+            self.questions[question_index].get_answers(),
+            self.layout.answers[question_index].clone(),
+        )
+    }
+
+    /// The question at `question_index` in the quiz file as a flashcard.
+    fn card(&self, question_index: usize, mode: Mode) -> flashcard::Card {
+        flashcard::Card::new(
+            &self.questions[question_index],
+            mode == Mode::ReversedFlashcards,
+        )
+    }
+
+    /// Whether more than one answer of the question is correct.
+    fn select_all(&self, question_index: usize) -> bool {
+        self.questions[question_index]
+            .get_answers()
+            .correct_answers()
+            .is_some_and(|x| x.len() > 1)
+    }
+
+    /// The question at `question_index` in the quiz file, rendered with its answers.
+    fn question_text(&self, question_index: usize) -> String {
+        let question = &self.questions[question_index];
+        let answers = self.answers(question_index).answers();
+        let question_type = question.get_type();
+        let question_str = question.get_question();
+
+        let question_text = match question_type {
+            QuestionType::TrueFalse => {
+                let answers = answers.unwrap();
+                let question_template = template::TrueFalseQuestionTemplate::new(
+                    question_str,
+                    &answers[0],
+                    &answers[1],
+                );
+                question_template.render().unwrap()
+            }
+            QuestionType::Selection => {
+                let answers = answers.unwrap();
+                let mut answers_str = Vec::new();
+                for x in &answers {
+                    answers_str.push(x.as_str());
+                }
+
+                let question_template = template::SelectionQuestionTemplate::new(
+                    question_str,
+                    answers_str,
+                    self.select_all(question_index),
+                );
+                question_template.render().unwrap()
+            }
+            QuestionType::UserInput => {
+                let question_template = template::UserInputQuestionTemplate::new(question_str);
+                question_template.render().unwrap()
+            }
+            QuestionType::Order => {
+                let answers = answers.unwrap();
+                let mut answers_str = Vec::new();
+                for x in &answers {
+                    answers_str.push(x.as_str());
+                }
+
+                let question_template =
+                    template::OrderQuestionTemplate::new(question_str, answers_str);
+                question_template.render().unwrap()
+            }
+        };
+        question_text
+    }
+}
+
+/// Grades the answer to the question asked at `position`, returning the row
+/// shown for it along with the score it earned.
+fn question_result(
+    display: &QuestionListDisplay,
+    position: usize,
+    answer: Option<&str>,
+    options: quizlit::GradeOptions,
+    time: Option<Duration>,
+) -> (template::QuestionResult, f32) {
+    let index = display.layout.questions[position];
+    let question = &display.questions[index];
+    let question_answers = display.answers(index);
+    let grade = question_answers.validate_answer(answer.unwrap_or(""), options);
+
+    let (status, score, incorrect_explanations, note) = match answer {
+        Some(_) => (
+            format!("{:?}", grade.status),
+            grade.score,
+            grade.incorrect_explanations,
+            grade.note,
+        ),
+        None => ("Unanswered".to_string(), 0.0, Vec::new(), None),
+    };
+
+    let result = template::QuestionResult {
+        number: position + 1,
+        question: question.get_question().to_string(),
+        status,
+        answer: answer.map(|x| question_answers.display_answer(x)),
+        expected: grade.expected,
+        explanations: grade.explanations,
+        incorrect_explanations,
+        note,
+        time: time.map(|x| template::format_clock(x.as_secs())),
+        self_graded: false,
+    };
+    (result, score)
+}
+
+/// The row shown for the flashcard asked at `position`, along with the score
+/// the grade given to it earned.
+fn card_result(
+    display: &QuestionListDisplay,
+    position: usize,
+    grade: Option<SelfGrade>,
+    mode: Mode,
+    time: Option<Duration>,
+) -> (template::QuestionResult, f32) {
+    let card = display.card(display.layout.questions[position], mode);
+    let result = template::QuestionResult {
+        number: position + 1,
+        question: card.front,
+        status: grade.map_or("Unanswered".to_string(), |x| format!("{x:?}")),
+        answer: None,
+        expected: card.back,
+        explanations: card.explanations,
+        incorrect_explanations: Vec::new(),
+        note: None,
+        time: time.map(|x| template::format_clock(x.as_secs())),
+        self_graded: true,
+    };
+    (result, grade.map_or(0.0, SelfGrade::score))
+}
+
+fn results_report(
+    display: &QuestionListDisplay,
+    answers: &HashMap<usize, String>,
+    spent: &HashMap<usize, Duration>,
+    seed: u64,
+    options: quizlit::GradeOptions,
+    mode: Mode,
+    round: usize,
+) -> askama::Result<String> {
+    let mut score = 0.0;
+    let mut results = Vec::new();
+
+    for (position, index) in display.layout.questions.iter().enumerate() {
+        let answer = answers.get(index).map(|x| x.as_str());
+        let time = spent.get(index).copied();
+        let (result, question_score) = match mode {
+            Mode::Quiz => question_result(display, position, answer, options, time),
+            _ => card_result(
+                display,
+                position,
+                answer.and_then(SelfGrade::parse),
+                mode,
+                time,
+            ),
+        };
+        score += question_score;
+        results.push(result);
+    }
+
+    template::ResultsTemplate::new(score, seed, results)
+        .with_round(round)
+        .render()
+}
+
+/// Where a session reads commands and answers from.
+pub trait Input {
+    /// The next line without its line ending, `None` once the input runs out.
+    async fn read_line(&mut self) -> std::io::Result<Option<String>>;
+}
+
+/// Reads a line from `reader` without its line ending.
+fn read_line(reader: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut line = String::new();
+    match reader.read_line(&mut line)? {
+        0 => Ok(None),
+        _ => Ok(Some(line.trim_end_matches(['\n', '\r']).to_string())),
+    }
+}
+
+/// Readers that never block for long, such as a script of answers.
+impl<T: BufRead> Input for T {
+    async fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        read_line(self)
+    }
+}
+
+/// Reads lines from a blocking reader, like stdin, on a thread of its own so
+/// that waiting for the user does not keep time limits from running out.
+pub struct BackgroundInput {
+    lines: mpsc::Receiver<std::io::Result<String>>,
+}
+
+impl BackgroundInput {
+    pub fn new(mut reader: Box<dyn BufRead + Send>) -> Self {
+        let (sender, lines) = mpsc::channel(1);
+        std::thread::spawn(move || loop {
+            let line = match read_line(&mut reader) {
+                Ok(Some(line)) => Ok(line),
+                Ok(None) => return,
+                Err(error) => Err(error),
+            };
+            // The session is over once nobody listens anymore.
+            if sender.blocking_send(line).is_err() {
+                return;
+            }
+        });
+
+        Self { lines }
+    }
+}
+
+impl Input for BackgroundInput {
+    async fn read_line(&mut self) -> std::io::Result<Option<String>> {
+        self.lines.recv().await.transpose()
+    }
+}
+
+/// Where a session shows its events.
+pub trait Output {
+    fn render(&mut self, event: &Event) -> std::io::Result<()>;
+}
+
+/// The current question, for front-ends that lay it out themselves.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QuestionView {
+    /// Position of the question in the order the questions are asked.
+    pub position: usize,
+    pub total: usize,
+    pub answered: usize,
+    pub kind: QuestionType,
+    pub question: String,
+    /// The answers in the order they are shown, `None` for free text questions.
+    pub options: Option<Vec<String>>,
+    /// More than one of the options is correct.
+    pub select_all: bool,
+    pub flagged: bool,
+    /// The question is shown as a flashcard, `question` is its front.
+    pub flashcard: bool,
+    /// The back of the flashcard once it is revealed.
+    pub back: Option<String>,
+}
+
+/// Something a front-end has to show.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event {
+    /// The question asked at `position`, `notice` tells how the last input was handled.
+    Question {
+        position: usize,
+        notice: Option<String>,
+        text: String,
+        /// The time left when the event was created, see `Deadlines::describe`.
+        time_left: Option<String>,
+    },
+    /// Practice mode feedback on the answer that was just given.
+    Feedback { text: String },
+    /// Every question with the answer given so far, before submitting.
+    Review {
+        notice: Option<String>,
+        text: String,
+        time_left: Option<String>,
+    },
+    /// The results report, the session is over.
+    Results { text: String },
+    /// The session was saved to be resumed instead of being submitted.
+    Saved { text: String },
+}
+
+/// How the questions of a quiz are gone through.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Mode {
+    /// Questions are answered and graded.
+    #[default]
+    Quiz,
+    /// Questions are flashcards, their answer is revealed and graded by the user.
+    Flashcards,
+    /// Flashcards, with free text questions shown from their answer.
+    ReversedFlashcards,
+}
+
+/// How long the quiz, and each of its questions, may take.
+#[derive(Debug, PartialEq, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TimeLimits {
+    pub quiz: Option<Duration>,
+    /// Time for each question, spread over every time it is shown.
+    pub question: Option<Duration>,
+}
+
+/// When the time for the quiz and for the current question runs out.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Deadlines {
+    pub quiz: Option<Instant>,
+    pub question: Option<Instant>,
+}
+
+impl Deadlines {
+    /// The deadline that comes first.
+    pub fn next(&self) -> Option<Instant> {
+        match (self.quiz, self.question) {
+            (Some(quiz), Some(question)) => Some(quiz.min(question)),
+            (quiz, question) => quiz.or(question),
+        }
+    }
+
+    /// The time left at `now`, e.g. "04:10 left, 00:25 for this question".
+    pub fn describe(&self, now: Instant) -> Option<String> {
+        // Rounded up, so that the time is only up at "00:00".
+        let left = |x: Instant| {
+            let left = x.saturating_duration_since(now);
+            template::format_clock(left.as_secs() + u64::from(left.subsec_nanos() > 0))
+        };
+        match (self.quiz, self.question) {
+            (Some(quiz), Some(question)) => Some(format!(
+                "{} left, {} for this question",
+                left(quiz),
+                left(question)
+            )),
+            (Some(quiz), None) => Some(format!("{} left", left(quiz))),
+            (None, Some(question)) => Some(format!("{} left for this question", left(question))),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    Asking,
+    Feedback,
+    Review,
+    Finished,
+    /// Over without being submitted, because the input ran out or the session
+    /// was saved to go on with later.
+    Interrupted,
+}
+
+/// How far a session got, everything needed to pick it up again.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Progress {
+    /// Position of the current question in the order the questions are asked.
+    pub position: usize,
+    /// Answers by the index of the question in the quiz file.
+    pub answers: BTreeMap<usize, String>,
+    pub flagged: BTreeSet<usize>,
+    /// Seconds spent on the quiz.
+    pub elapsed: u64,
+    /// Milliseconds spent on each question, by its index in the quiz file.
+    #[serde(default)]
+    pub spent: BTreeMap<usize, u64>,
+}
+
+/// A run through a quiz. The session turns lines of input into events and
+/// does not do any IO itself, see `run`, apart from saving its progress
+/// when it has a store.
+pub struct QuizSession {
+    display: QuestionListDisplay,
+    answers: HashMap<usize, String>,
+    /// Indexes in the quiz file of the flagged questions.
+    flagged: HashSet<usize>,
+    seed: u64,
+    options: quizlit::GradeOptions,
+    practice: bool,
+    mode: Mode,
+    /// Index in the quiz file of the flashcard whose answer is shown.
+    revealed: Option<usize>,
+    state: State,
+    notice: Option<String>,
+    /// Time spent before the session was resumed.
+    elapsed: Duration,
+    started: Instant,
+    limits: TimeLimits,
+    /// Time spent on each question, by its index in the quiz file, up to `tick`.
+    spent: HashMap<usize, Duration>,
+    tick: Instant,
+    store: Option<(save::Store, save::QuizIdentity)>,
+    /// 1 for the first time through the quiz, then 2, 3, … for every retry.
+    round: usize,
+}
+
+impl QuizSession {
+    /// `practice` shows feedback after every answer.
+    pub fn new(
+        questions: Vec<quizlit::Question>,
+        layout: quizlit::Layout,
+        seed: u64,
+        options: quizlit::GradeOptions,
+        practice: bool,
+    ) -> Self {
+        Self {
+            display: QuestionListDisplay::new(questions, layout),
+            answers: HashMap::new(),
+            flagged: HashSet::new(),
+            seed,
+            options,
+            practice,
+            mode: Mode::default(),
+            revealed: None,
+            state: State::Asking,
+            notice: None,
+            elapsed: Duration::ZERO,
+            started: Instant::now(),
+            limits: TimeLimits::default(),
+            spent: HashMap::new(),
+            tick: Instant::now(),
+            store: None,
+            round: 1,
+        }
+    }
+
+    /// Submits the answers when the time for the quiz runs out, and moves on
+    /// when the time for a question does.
+    pub fn with_time_limits(mut self, limits: TimeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Shows the questions as flashcards, unless `mode` is `Mode::Quiz`.
+    pub fn with_mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Saves the progress of the session to `store` after every line of
+    /// input, and deletes it once the session is over.
+    pub fn with_store(mut self, store: save::Store, quiz: save::QuizIdentity) -> Self {
+        self.store = Some((store, quiz));
+        self
+    }
+
+    /// Where the progress of the session is saved, if it is kept at all.
+    pub fn store(&self) -> Option<&save::Store> {
+        self.store.as_ref().map(|(store, _)| store)
+    }
+
+    /// Picks up where a saved session left off.
+    pub fn restore(&mut self, progress: Progress) {
+        self.answers = progress.answers.into_iter().collect();
+        self.flagged = progress.flagged.into_iter().collect();
+        self.display.cursor.seek(progress.position);
+        self.elapsed = Duration::from_secs(progress.elapsed);
+        self.spent = progress
+            .spent
+            .into_iter()
+            .map(|(index, spent)| (index, Duration::from_millis(spent)))
+            .collect();
+    }
+
+    pub fn progress(&self) -> Progress {
+        Progress {
+            position: self.display.cursor.current_position().unwrap_or(0),
+            answers: self.answers.clone().into_iter().collect(),
+            flagged: self.flagged.iter().copied().collect(),
+            elapsed: self.elapsed().as_secs(),
+            spent: self
+                .spent
+                .iter()
+                .map(|(index, spent)| (*index, spent.as_millis() as u64))
+                .collect(),
+        }
+    }
+
+    /// Every answered question along with how well its answer was
+    /// remembered, see `study::quality`.
+    pub fn recall(&self) -> Vec<(&quizlit::Question, u8)> {
+        self.display
+            .layout
+            .questions
+            .iter()
+            .filter_map(|index| {
+                let answer = self.answers.get(index)?;
+                let quality = match self.mode {
+                    Mode::Quiz => study::quality(
+                        &self
+                            .display
+                            .answers(*index)
+                            .validate_answer(answer, self.options),
+                    ),
+                    _ => SelfGrade::parse(answer)?.quality(),
+                };
+                Some((&self.display.questions[*index], quality))
+            })
+            .collect()
+    }
+
+    /// A new session on the questions that were not answered correctly, in a
+    /// new order, `None` when there are none. `shuffle` also shuffles their
+    /// options again. The time limits, mode and grading stay the same.
+    pub fn retry(&self, seed: u64, shuffle: bool) -> Option<QuizSession> {
+        let missed: Vec<quizlit::Question> = self
+            .display
+            .layout
+            .questions
+            .iter()
+            .zip(self.outcomes())
+            .filter(|(_, outcome)| outcome.status != history::Status::Correct)
+            .map(|(index, _)| self.display.questions[*index].clone())
+            .collect();
+        if missed.is_empty() {
+            return None;
+        }
+
+        let layout = quizlit::Layout::new(&missed, seed, shuffle, true);
+        let mut session = QuizSession::new(missed, layout, seed, self.options, self.practice)
+            .with_time_limits(self.limits)
+            .with_mode(self.mode);
+        session.round = self.round + 1;
+        Some(session)
+    }
+
+    /// How every question went, in the order they were asked.
+    pub fn outcomes(&self) -> Vec<history::Outcome> {
+        self.display
+            .layout
+            .questions
+            .iter()
+            .map(|index| {
+                let (status, score) = match self.answers.get(index) {
+                    Some(answer) if self.mode != Mode::Quiz => SelfGrade::parse(answer)
+                        .map_or((history::Status::Unanswered, 0.0), |x| {
+                            (x.status(), x.score())
+                        }),
+                    Some(answer) => {
+                        let grade = self
+                            .display
+                            .answers(*index)
+                            .validate_answer(answer, self.options);
+                        (history::Status::from(&grade.status), grade.score)
+                    }
+                    None => (history::Status::Unanswered, 0.0),
+                };
+                history::Outcome {
+                    question: self.display.questions[*index].get_question().to_string(),
+                    status,
+                    score,
+                    time: self.spent.get(index).map_or(0, |x| x.as_secs()),
+                }
+            })
+            .collect()
+    }
+
+    /// Time spent on the quiz, including before it was resumed.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed + self.started.elapsed()
+    }
+
+    /// When the time for the quiz, and for the current question, runs out.
+    pub fn deadlines(&self) -> Deadlines {
+        // Limits too far in the future to be represented are no limit at all.
+        let now = Instant::now();
+        let quiz = self
+            .limits
+            .quiz
+            .and_then(|x| now.checked_add(x.saturating_sub(self.elapsed())));
+        // There is no deadline for a question whose time is up already.
+        let question = match (
+            self.state,
+            self.limits.question,
+            self.display.cursor.current(),
+        ) {
+            (State::Asking, Some(limit), Some(index)) => {
+                let spent = self.spent.get(&index).copied().unwrap_or_default();
+                limit
+                    .checked_sub(spent)
+                    .filter(|x| !x.is_zero())
+                    .and_then(|x| self.tick.checked_add(x))
+            }
+            _ => None,
+        };
+
+        Deadlines { quiz, question }
+    }
+
+    /// Handles the time running out, called once the next of the `deadlines` passed.
+    pub fn timeout(&mut self) -> askama::Result<Event> {
+        self.track_time();
+
+        let event = if self.limits.quiz.is_some_and(|x| self.elapsed() >= x) {
+            let Event::Results { text } = self.finish()? else {
+                unreachable!("finishing always shows the results");
+            };
+            Event::Results {
+                text: format!("Time is up!\n\n{text}"),
+            }
+        } else {
+            match (self.state, self.display.cursor.current_position()) {
+                (State::Asking, Some(position))
+                    if self.is_time_up(self.display.layout.questions[position]) =>
+                {
+                    self.notice = Some(format!("Time is up for question {}", position + 1));
+                    self.next_question()?
+                }
+                (State::Review, _) => self.review()?,
+                (State::Feedback, _) => {
+                    self.state = State::Asking;
+                    self.next_question()?
+                }
+                _ => self.question_event()?,
+            }
+        };
+        self.autosave();
+
+        Ok(event)
+    }
+
+    /// Shows `notice` along with the next event.
+    pub fn notify(&mut self, notice: String) {
+        self.notice = Some(notice);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, State::Finished | State::Interrupted)
+    }
+
+    /// Whether the user submitted or quit, as opposed to saving the session
+    /// or the input running out, in which case it is kept to be resumed.
+    pub fn is_submitted(&self) -> bool {
+        self.state == State::Finished
+    }
+
+    /// 1 for the first time the questions are asked, then one more for
+    /// every retry of the missed ones.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// The question being asked, `None` once the session is over.
+    pub fn view(&self) -> Option<QuestionView> {
+        if self.is_finished() {
+            return None;
+        }
+
+        let position = self.display.cursor.current_position()?;
+        let index = self.display.layout.questions[position];
+        let question = &self.display.questions[index];
+        let view = QuestionView {
+            position,
+            total: self.display.cursor.total(),
+            answered: self.answers.len(),
+            kind: question.get_type().clone(),
+            question: question.get_question().to_string(),
+            options: self.display.answers(index).answers(),
+            select_all: self.display.select_all(index),
+            flagged: self.flagged.contains(&index),
+            flashcard: false,
+            back: None,
+        };
+        if self.mode == Mode::Quiz {
+            return Some(view);
+        }
+
+        let card = self.display.card(index, self.mode);
+        let back = card
+            .explanations
+            .iter()
+            .fold(card.back.join(", "), |back, x| format!("{back}\n\n{x}"));
+        Some(QuestionView {
+            question: card.front,
+            options: None,
+            select_all: false,
+            flashcard: true,
+            back: Some(back).filter(|_| self.revealed == Some(index)),
+            ..view
+        })
+    }
+
+    /// The event showing the current question.
+    pub fn start(&mut self) -> askama::Result<Event> {
+        self.tick = Instant::now();
+        self.question_event()
+    }
+
+    /// Handles a line of input, `None` meaning the input ran out, and
+    /// returns what to show next.
+    pub fn handle(&mut self, line: Option<&str>) -> askama::Result<Event> {
+        let event = self.handle_line(line)?;
+        // When the input runs out, e.g. because the terminal was closed, the
+        // session is kept so it can be resumed.
+        if line.is_some() {
+            self.autosave();
+        }
+
+        Ok(event)
+    }
+
+    fn handle_line(&mut self, line: Option<&str>) -> askama::Result<Event> {
+        self.track_time();
+        let Some(line) = line else {
+            let event = self.finish()?;
+            self.state = State::Interrupted;
+            return Ok(event);
+        };
+
+        match self.state {
+            State::Feedback => {
+                self.state = State::Asking;
+                return self.next_question();
+            }
+            State::Review => return self.handle_review(line),
+            State::Asking | State::Finished | State::Interrupted => {}
+        }
+
+        let command = match command::parse(line) {
+            // Enter shows the answer of a flashcard.
+            Err(_) if self.mode != Mode::Quiz && line.trim().is_empty() => {
+                Ok(Command::Answer(String::new()))
+            }
+            command => command,
+        };
+        match command {
+            Ok(command) => self.run_command(command),
+            Err(error) => {
+                self.notice = Some(error);
+                self.question_event()
+            }
+        }
+    }
+
+    /// On the review screen a question number goes to that question and an
+    /// empty line goes back to the current one, commands work as usual.
+    fn handle_review(&mut self, line: &str) -> askama::Result<Event> {
+        let line = line.trim();
+        if line.is_empty() {
+            self.state = State::Asking;
+            return self.question_event();
+        }
+
+        let command = match line.parse::<usize>() {
+            Ok(number) if number > 0 => Ok(Command::Goto(number)),
+            _ => command::parse(line),
+        };
+        match command {
+            Ok(Command::Answer(_)) => {
+                self.notice =
+                    Some("Type the number of the question to change its answer".to_string());
+                self.review()
+            }
+            Ok(command) => {
+                self.state = State::Asking;
+                self.run_command(command)
+            }
+            Err(error) => {
+                self.notice = Some(error);
+                self.review()
+            }
+        }
+    }
+
+    fn run_command(&mut self, command: Command) -> askama::Result<Event> {
+        let Some(position) = self.display.cursor.current_position() else {
+            return self.finish();
+        };
+        let index = self.display.layout.questions[position];
+
+        match command {
+            Command::Next => self.advance(),
+            Command::Previous => {
+                if self.display.cursor.next_back().is_none() {
+                    self.notice = Some("No previous question".to_string());
+                }
+            }
+            Command::Goto(number) => {
+                if !self.seek(number - 1) {
+                    self.notice = Some(format!("There is no question {number}"));
+                }
+            }
+            Command::Skip => self.skip(position),
+            Command::Flag => {
+                let flagged = self.flagged.insert(index);
+                if !flagged {
+                    self.flagged.remove(&index);
+                }
+                self.notice = Some(match flagged {
+                    true => format!("Flagged question {}", position + 1),
+                    false => format!("Unflagged question {}", position + 1),
+                });
+            }
+            Command::List => self.notice = Some(self.list()),
+            Command::Only(filter) => self.show_only(filter),
+            Command::Hint => self.notice = Some(self.display.answers(index).hint()),
+            Command::Help => self.notice = Some(command::help()),
+            Command::Review => return self.review(),
+            Command::Submit | Command::Quit => return self.finish(),
+            Command::Save => return self.save(),
+            Command::Answer(answer) => {
+                if self.is_time_up(index) {
+                    self.notice = Some(format!("The time for question {} is up", position + 1));
+                    return self.question_event();
+                }
+                if self.mode != Mode::Quiz {
+                    return self.handle_card(index, &answer);
+                }
+                if let Err(error) = self.display.answers(index).check_input(&answer) {
+                    self.notice = Some(format!("Invalid answer: {error}"));
+                    return self.question_event();
+                }
+
+                self.answers.insert(index, answer.clone());
+                if self.practice {
+                    self.state = State::Feedback;
+                    let (result, _) = question_result(
+                        &self.display,
+                        position,
+                        Some(answer.as_str()),
+                        self.options,
+                        self.spent.get(&index).copied(),
+                    );
+                    let text = template::FeedbackTemplate::new(result).render()?;
+                    return Ok(Event::Feedback { text });
+                }
+                return self.next_question();
+            }
+        }
+
+        // Coming back to a flashcard shows its front again.
+        if self.display.cursor.current_position() != Some(position) {
+            self.revealed = None;
+        }
+        self.question_event()
+    }
+
+    /// Reveals the answer of the flashcard at `index`, or grades it with
+    /// `input` once it is revealed.
+    fn handle_card(&mut self, index: usize, input: &str) -> askama::Result<Event> {
+        if self.revealed != Some(index) {
+            self.revealed = Some(index);
+            return self.question_event();
+        }
+
+        match SelfGrade::parse(input) {
+            Some(grade) => {
+                self.answers.insert(index, grade.name().to_string());
+                self.next_question()
+            }
+            None => {
+                self.notice = Some(
+                    "Grade the card with 1 (again), 2 (hard), 3 (good) or 4 (easy)".to_string(),
+                );
+                self.question_event()
+            }
+        }
+    }
+
+    /// Feeds `input` to the session and renders every event on `output`
+    /// until the session is over.
+    pub async fn run(
+        &mut self,
+        input: &mut impl Input,
+        output: &mut impl Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut event = self.start()?;
+        loop {
+            output.render(&event)?;
+            if self.is_finished() {
+                return Ok(());
+            }
+
+            event = match self.deadlines().next() {
+                Some(deadline) => tokio::select! {
+                    line = input.read_line() => self.handle(line?.as_deref())?,
+                    _ = tokio::time::sleep_until(deadline.into()) => self.timeout()?,
+                },
+                None => self.handle(input.read_line().await?.as_deref())?,
+            };
+        }
+    }
+
+    /// Moves to the first unanswered question after `position`, starting
+    /// over from the first question at the end.
+    fn skip(&mut self, position: usize) {
+        let total = self.display.cursor.total();
+        let unanswered = (1..total).map(|x| (position + x) % total).find(|x| {
+            !self
+                .answers
+                .contains_key(&self.display.layout.questions[*x])
+        });
+
+        if !unanswered.is_some_and(|x| self.seek(x)) {
+            self.notice = Some("There is no other unanswered question".to_string());
+        }
+    }
+
+    /// Moves to the question asked at `position`, showing every question again
+    /// when `:only` hid it. False when there is no such question.
+    fn seek(&mut self, position: usize) -> bool {
+        let cursor = &mut self.display.cursor;
+        if cursor.seek(position).is_none() && position < cursor.total() {
+            cursor.show_all();
+            cursor.seek(position);
+        }
+
+        cursor.current_position() == Some(position)
+    }
+
+    /// Hides the questions that do not pass `filter` from `:next` and
+    /// `:previous`. The filter is applied once, a question answered since
+    /// stays visible until the next `:only`.
+    fn show_only(&mut self, filter: Filter) {
+        let keep = |index: usize| match filter {
+            Filter::Unanswered => !self.answers.contains_key(&index),
+            Filter::Flagged => self.flagged.contains(&index),
+            Filter::All => true,
+        };
+        if !self.display.layout.questions.iter().any(|x| keep(*x)) {
+            self.notice = Some(format!("There are no {} questions", filter.name()));
+            return;
+        }
+
+        self.display.cursor.show_only(keep);
+        let count = self.display.cursor.visible();
+        self.notice = Some(match (filter, count) {
+            (Filter::All, _) => format!("Going through all {count} questions"),
+            (_, 1) => format!(
+                "Going through the only {} question, {PREFIX}only all shows every question again",
+                filter.name()
+            ),
+            _ => format!(
+                "Going through the {count} {} questions, {PREFIX}only all shows every question again",
+                filter.name()
+            ),
+        });
+    }
+
+    /// Every question with whether it is answered or flagged, the current one marked with ">".
+    fn list(&self) -> String {
+        let current = self.display.cursor.current_position();
+        let lines: Vec<String> = self
+            .display
+            .layout
+            .questions
+            .iter()
+            .enumerate()
+            .map(|(position, index)| {
+                let mut status = match self.answers.contains_key(index) {
+                    true => "Answered".to_string(),
+                    false => "Unanswered".to_string(),
+                };
+                if self.flagged.contains(index) {
+                    status.push_str(", flagged");
+                }
+                let marker = match current == Some(position) {
+                    true => ">",
+                    false => " ",
+                };
+
+                format!(
+                    "{marker} {}. [{status}] {}",
+                    position + 1,
+                    self.display.questions[*index].get_question()
+                )
+            })
+            .collect();
+
+        lines.join("\n")
+    }
+
+    fn autosave(&mut self) {
+        let Some((store, quiz)) = &self.store else {
+            return;
+        };
+
+        let result = match self.is_submitted() {
+            true => store.remove(),
+            false => store.write(&save::SavedSession {
+                quiz: quiz.clone(),
+                seed: self.seed,
+                layout: self.display.layout.clone(),
+                options: self.options,
+                practice: self.practice,
+                mode: self.mode,
+                progress: self.progress(),
+                limits: self.limits,
+                saved_at: save::now(),
+            }),
+        };
+        if let Err(error) = result {
+            self.notice = Some(format!("Unable to save the session: {error}"));
+        }
+    }
+
+    /// Adds the time since the last tick to the question being asked.
+    fn track_time(&mut self) {
+        let now = Instant::now();
+        if let (State::Asking, Some(index)) = (self.state, self.display.cursor.current()) {
+            *self.spent.entry(index).or_default() += now - self.tick;
+        }
+        self.tick = now;
+    }
+
+    fn is_time_up(&self, index: usize) -> bool {
+        match (self.limits.question, self.spent.get(&index)) {
+            (Some(limit), Some(spent)) => *spent >= limit,
+            _ => false,
+        }
+    }
+
+    fn advance(&mut self) {
+        if self.display.cursor.next().is_none() {
+            self.notice = Some("No next question".to_string());
+        }
+    }
+
+    /// Moves on after an answer, to the review once there is no next question.
+    /// Flashcards have nothing to review, their session is over instead.
+    fn next_question(&mut self) -> askama::Result<Event> {
+        match (self.display.cursor.next(), self.mode) {
+            (Some(_), _) => self.question_event(),
+            (None, Mode::Quiz) => self.review(),
+            (None, _) => self.finish(),
+        }
+    }
+
+    /// The review screen with every question and the answer given to it.
+    fn review(&mut self) -> askama::Result<Event> {
+        self.state = State::Review;
+        let rows = self
+            .display
+            .layout
+            .questions
+            .iter()
+            .enumerate()
+            .map(|(position, index)| template::ReviewRow {
+                number: position + 1,
+                question: self.display.questions[*index].get_question().to_string(),
+                answer: self.answers.get(index).map(|x| match self.mode {
+                    Mode::Quiz => self.display.answers(*index).display_answer(x),
+                    _ => x.clone(),
+                }),
+                flagged: self.flagged.contains(index),
+            })
+            .collect();
+
+        Ok(Event::Review {
+            notice: self.notice.take(),
+            text: template::ReviewTemplate::new(rows).render()?,
+            time_left: self.deadlines().describe(Instant::now()),
+        })
+    }
+
+    /// The event showing the current question, the results when there is none.
+    fn question_event(&mut self) -> askama::Result<Event> {
+        let (Some(position), Some(index)) = (
+            self.display.cursor.current_position(),
+            self.display.cursor.current(),
+        ) else {
+            return self.finish();
+        };
+
+        let text = match self.mode {
+            Mode::Quiz => self.display.question_text(index),
+            _ => {
+                let card = self.display.card(index, self.mode);
+                template::FlashcardTemplate::new(&card, self.revealed == Some(index)).render()?
+            }
+        };
+        Ok(Event::Question {
+            position,
+            notice: self.notice.take(),
+            text,
+            time_left: self.deadlines().describe(Instant::now()),
+        })
+    }
+
+    /// Stops the session without submitting it, keeping it in the store.
+    fn save(&mut self) -> askama::Result<Event> {
+        if self.store.is_none() {
+            self.notice = Some(
+                "This session can not be saved, quizzes read from stdin, study, \
+                 --only-missed and retry sessions and sessions run with --no-save are not \
+                 kept"
+                    .to_string(),
+            );
+            return self.question_event();
+        }
+
+        self.state = State::Interrupted;
+        self.autosave();
+        // The notice tells why the session could not be saved.
+        if self.notice.is_some() {
+            self.state = State::Asking;
+            return self.question_event();
+        }
+
+        Ok(Event::Saved {
+            text: "Stopped without submitting the answers".to_string(),
+        })
+    }
+
+    fn finish(&mut self) -> askama::Result<Event> {
+        self.state = State::Finished;
+        let text = results_report(
+            &self.display,
+            &self.answers,
+            &self.spent,
+            self.seed,
+            self.options,
+            self.mode,
+            self.round,
+        )?;
+        Ok(Event::Results { text })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    impl Output for Vec<Event> {
+        fn render(&mut self, event: &Event) -> std::io::Result<()> {
+            self.push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn session(practice: bool) -> QuizSession {
+        let quiz = json!({
+            "questions": [
+                {
+                    "kind": "true_false",
+                    "question": "Is the number 9 is afraid of the number 7?",
+                    "answers": {
+                        "correct": {"answer": "True"},
+                        "incorrect": {"answer": "False", "explanation": "7 8 (ate) 9"}
+                    }
+                },
+                {
+                    "kind": "selection",
+                    "question": "What devil fruit did Luffy eat?",
+                    "answers": {
+                        "correct": [{"answer": "Gum Gum Fruit"}],
+                        "incorrect": [{"answer": "Buggy Buggy Fruit"}]
+                    }
+                }
+            ]
+        });
+        let questions = quizlit::Quiz::new(&quiz).unwrap().questions;
+        let layout = quizlit::Layout::new(&questions, 7, false, false);
+
+        QuizSession::new(
+            questions,
+            layout,
+            7,
+            quizlit::GradeOptions::default(),
+            practice,
+        )
+    }
+
+    /// Runs a session on `script` and describes every event it rendered.
+    async fn run(session: QuizSession, script: &str) -> Vec<String> {
+        run_with(session, &mut script.as_bytes()).await
+    }
+
+    async fn run_with(mut session: QuizSession, input: &mut impl Input) -> Vec<String> {
+        let mut events = Vec::new();
+        session.run(input, &mut events).await.unwrap();
+        assert!(session.is_finished());
+
+        events
+            .into_iter()
+            .map(|event| match event {
+                Event::Question {
+                    position, notice, ..
+                } => match notice {
+                    Some(notice) => format!("question {position}: {notice}"),
+                    None => format!("question {position}"),
+                },
+                Event::Feedback { text } => format!("feedback {}", text.lines().next().unwrap()),
+                Event::Review { notice, .. } => match notice {
+                    Some(notice) => format!("review: {notice}"),
+                    None => "review".to_string(),
+                },
+                Event::Results { text } | Event::Saved { text } => {
+                    text.lines().next().unwrap().to_string()
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_session_run() {
+        let cases = vec![
+            (
+                "answer everything",
+                "true\na\n:q\n",
+                false,
+                vec!["question 0", "question 1", "review", "Results: 2/2 (100%)"],
+            ),
+            (
+                "navigation",
+                ":p\n:n\n:n\n:p\n:q\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 0: No previous question",
+                    "question 1",
+                    "question 1: No next question",
+                    "question 0",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "invalid answer",
+                ":n\nc\nb\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "question 1: Invalid answer: \"c\" is not one of the options",
+                    "review",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "bare words are answers",
+                "true\nq\n:q\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "question 1: Invalid answer: \"q\" is not one of the options",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "only some questions",
+                "True\n:flag\n:only flagged\n:p\n:goto 1\n:only unanswered\n:only all\n:p\n:q\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "question 1: Flagged question 2",
+                    "question 1: Going through the only flagged question, :only all shows every \
+                     question again",
+                    "question 1: No previous question",
+                    "question 0",
+                    "question 1: Going through the only unanswered question, :only all shows \
+                     every question again",
+                    "question 1: Going through all 2 questions",
+                    "question 0",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "only without matching questions",
+                ":only flagged\n:q\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 0: There are no flagged questions",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "save without a store",
+                ":save\n:q\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 0: This session can not be saved, quizzes read from stdin, \
+                     study, --only-missed and retry sessions and sessions run with --no-save \
+                     are not kept",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "practice",
+                "False\n\na\n",
+                true,
+                vec![
+                    "question 0",
+                    "feedback 1. [Incorrect] Is the number 9 is afraid of the number 7?",
+                    "question 1",
+                    "feedback 2. [Correct] What devil fruit did Luffy eat?",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "no input",
+                "",
+                false,
+                vec!["question 0", "Results: 0/2 (0%)"],
+            ),
+            (
+                "commands",
+                ":goto 2\n:goto 3\n:g 1\n:skip\n:skip\n\n:jump\n:Quit\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "question 1: There is no question 3",
+                    "question 0",
+                    "question 1",
+                    "question 0",
+                    "question 0: Type an answer, or :help for the commands",
+                    "question 0: Unknown command \":jump\", :help lists the commands",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "skip answered questions",
+                ":n\na\n:skip\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "review",
+                    "question 0",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "flag and list",
+                "True\n:flag\n:list\n:flag\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "question 1: Flagged question 2",
+                    "question 1:   1. [Answered] Is the number 9 is afraid of the number 7?\n\
+                     > 2. [Unanswered, flagged] What devil fruit did Luffy eat?",
+                    "question 1: Unflagged question 2",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "review",
+                "True\n:flag\n:review\nGum\n1\n:review\n\n:submit\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 1",
+                    "question 1: Flagged question 2",
+                    "review",
+                    "review: Type the number of the question to change its answer",
+                    "question 0",
+                    "review",
+                    "question 0",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "hint",
+                ":hint\n:n\n:hint\n",
+                false,
+                vec![
+                    "question 0",
+                    "question 0: There is no hint for this question",
+                    "question 1",
+                    "question 1: It is not b. Buggy Buggy Fruit",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+        ];
+
+        for (case_name, script, practice, expected) in cases {
+            assert_eq!(
+                run(session(practice), script).await,
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_review() {
+        let mut session = session(false);
+        session.start().unwrap();
+        session.handle(Some(":next")).unwrap();
+        session.handle(Some(":flag")).unwrap();
+
+        let Event::Review { text, .. } = session.handle(Some("a")).unwrap() else {
+            panic!("expected the review");
+        };
+        assert!(text.starts_with("Review: 1/2 answered, 1 flagged"));
+        assert!(text.contains(
+            "1. Is the number 9 is afraid of the number 7?\n   Your answer:    (unanswered)"
+        ));
+        assert!(text.contains(
+            "2. [Flagged] What devil fruit did Luffy eat?\n   Your answer:    a. Gum Gum Fruit"
+        ));
+
+        let Event::Question { position, .. } = session.handle(Some("2")).unwrap() else {
+            panic!("expected a question");
+        };
+        assert_eq!(position, 1);
+        assert!(!session.is_finished());
+    }
+
+    #[test]
+    fn test_session_restore() {
+        let mut original = session(false);
+        original.start().unwrap();
+        original.handle(Some("True")).unwrap();
+        original.handle(Some(":flag")).unwrap();
+
+        let progress = original.progress();
+        assert_eq!(progress.position, 1);
+        assert_eq!(progress.answers, [(0, "True".to_string())].into());
+        assert_eq!(progress.flagged, [1].into());
+
+        let mut resumed = session(false);
+        resumed.restore(Progress {
+            elapsed: 90,
+            ..progress.clone()
+        });
+        assert_eq!(resumed.view(), original.view());
+        assert!(resumed.elapsed().as_secs() >= 90);
+        assert_eq!(resumed.progress().answers, progress.answers);
+    }
+
+    #[test]
+    fn test_session_autosave() {
+        let dir =
+            std::env::temp_dir().join(format!("terminal_quizlit-session-{}", std::process::id()));
+        let quiz = save::QuizIdentity {
+            source: "quiz.json".to_string(),
+            hash: "0123".to_string(),
+            fingerprint: "4567".to_string(),
+        };
+        let store = save::Store::new(&dir, &quiz, 7);
+        let mut session = session(false).with_store(store.clone(), quiz);
+
+        session.start().unwrap();
+        assert!(!store.exists());
+        session.handle(Some("True")).unwrap();
+        let (_, saved) = save::find(&dir, "0123").unwrap();
+        assert_eq!(saved.progress, session.progress());
+
+        // Running out of input or saving keeps the session, quitting deletes it.
+        session.handle(None).unwrap();
+        assert!(store.exists());
+        let Event::Saved { text } = session.handle(Some(":save")).unwrap() else {
+            panic!("expected the session to be saved");
+        };
+        assert_eq!(text, "Stopped without submitting the answers");
+        assert!(session.is_finished());
+        assert!(!session.is_submitted());
+        assert!(store.exists());
+        assert_eq!(session.store().map(|x| x.id()), Some(store.id()));
+
+        // Retries are not kept, so they have nothing to resume.
+        assert!(session.retry(7, false).unwrap().store().is_none());
+        session.handle(Some(":quit")).unwrap();
+        assert!(!store.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_session_submitted() {
+        let cases = vec![
+            ("quit", "True\n:quit\n", true),
+            ("submit", "True\n:submit\n", true),
+            ("input ran out", "True\n", false),
+            ("no input", "", false),
+        ];
+
+        for (case_name, script, expected) in cases {
+            let mut session = session(false);
+            session
+                .run(&mut script.as_bytes(), &mut Vec::new())
+                .await
+                .unwrap();
+            assert!(session.is_finished(), "case: {case_name} failed");
+            assert_eq!(session.is_submitted(), expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_session_recall() {
+        let mut session = session(false);
+        session.start().unwrap();
+        session.handle(Some("False")).unwrap();
+
+        let recall: Vec<(&str, u8)> = session
+            .recall()
+            .into_iter()
+            .map(|(question, quality)| (question.get_question(), quality))
+            .collect();
+        assert_eq!(
+            recall,
+            vec![("Is the number 9 is afraid of the number 7?", 0)]
+        );
+
+        let outcomes: Vec<(String, history::Status, f32)> = session
+            .outcomes()
+            .into_iter()
+            .map(|x| (x.question, x.status, x.score))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (
+                    "Is the number 9 is afraid of the number 7?".to_string(),
+                    history::Status::Incorrect,
+                    0.0
+                ),
+                (
+                    "What devil fruit did Luffy eat?".to_string(),
+                    history::Status::Unanswered,
+                    0.0
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_retry() {
+        let mut session = session(false);
+        session.start().unwrap();
+        session.handle(Some("False")).unwrap();
+        session.handle(Some("a")).unwrap();
+        session.handle(Some(":submit")).unwrap();
+
+        let retry = session.retry(3, true).unwrap();
+        assert_eq!(retry.round, 2);
+        assert_eq!(retry.display.questions.len(), 1);
+        let events = run(retry, "False\n:submit\n").await;
+        assert_eq!(
+            events,
+            vec!["question 0", "review", "Round 2 results: 0/1 (0%)"]
+        );
+
+        let mut retry = session.retry(3, true).unwrap();
+        retry.start().unwrap();
+        retry.handle(Some("True")).unwrap();
+        retry.handle(Some(":submit")).unwrap();
+        assert!(retry.retry(4, true).is_none());
+    }
+
+    #[test]
+    fn test_session_empty_quiz() {
+        let mut session = QuizSession::new(
+            vec![],
+            quizlit::Layout::new(&[], 7, true, true),
+            7,
+            quizlit::GradeOptions::default(),
+            false,
+        );
+
+        let Event::Results { text } = session.start().unwrap() else {
+            panic!("expected results");
+        };
+        assert!(text.starts_with("Results: 0/0 (0%)"));
+        assert!(session.is_finished());
+    }
+
+    #[test]
+    fn test_session_view() {
+        let mut session = session(false);
+        session.start().unwrap();
+        session.handle(Some("True")).unwrap();
+
+        let expected = QuestionView {
+            position: 1,
+            total: 2,
+            answered: 1,
+            kind: QuestionType::Selection,
+            question: "What devil fruit did Luffy eat?".to_string(),
+            options: Some(vec![
+                "Gum Gum Fruit".to_string(),
+                "Buggy Buggy Fruit".to_string(),
+            ]),
+            select_all: false,
+            flagged: false,
+            flashcard: false,
+            back: None,
+        };
+        assert_eq!(session.view(), Some(expected));
+
+        session.handle(Some(":quit")).unwrap();
+        assert_eq!(session.view(), None);
+    }
+
+    #[tokio::test]
+    async fn test_session_flashcards() {
+        let cases = vec![
+            (
+                "grade every card",
+                "\n3\n\n1\n",
+                vec![
+                    "question 0",
+                    "question 0",
+                    "question 1",
+                    "question 1",
+                    "Results: 1/2 (50%)",
+                ],
+            ),
+            (
+                "grade before revealing",
+                "3\ngreat\n\n:q\n",
+                vec![
+                    "question 0",
+                    "question 0",
+                    "question 0: Grade the card with 1 (again), 2 (hard), 3 (good) or 4 (easy)",
+                    "question 0: Grade the card with 1 (again), 2 (hard), 3 (good) or 4 (easy)",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "navigate",
+                "\n:n\n:p\n\nhard\n:q\n",
+                vec![
+                    "question 0",
+                    "question 0",
+                    "question 1",
+                    "question 0",
+                    "question 0",
+                    "question 1",
+                    "Results: 0.5/2 (25%)",
+                ],
+            ),
+        ];
+
+        for (case_name, script, expected) in cases {
+            let session = session(false).with_mode(Mode::Flashcards);
+            let events = run(session, script).await;
+            assert_eq!(events, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_session_flashcard() {
+        let quiz = json!({
+            "questions": [
+                {
+                    "kind": "user_input",
+                    "question": "What is the name Luffy uses in Dressrosa?",
+                    "answers": {"accepted": ["Lucy"], "explanation": "A disguise"}
+                }
+            ]
+        });
+        let questions = quizlit::Quiz::new(&quiz).unwrap().questions;
+        let layout = quizlit::Layout::new(&questions, 7, false, false);
+        let cases = vec![
+            (
+                Mode::Flashcards,
+                "What is the name Luffy uses in Dressrosa?",
+                "Lucy",
+            ),
+            (
+                Mode::ReversedFlashcards,
+                "Lucy",
+                "What is the name Luffy uses in Dressrosa?",
+            ),
+        ];
+
+        for (mode, front, back) in cases {
+            let mut session = QuizSession::new(
+                questions.clone(),
+                layout.clone(),
+                7,
+                quizlit::GradeOptions::default(),
+                false,
+            )
+            .with_mode(mode);
+            let case_name = format!("{mode:?}");
+
+            let Event::Question { text, .. } = session.start().unwrap() else {
+                panic!("case: {case_name} failed");
+            };
+            assert!(text.starts_with(&format!("Card: {front}\n\nPress enter")));
+            let view = session.view().unwrap();
+            assert_eq!(view.question, front, "case: {case_name} failed");
+            assert!(view.flashcard, "case: {case_name} failed");
+            assert_eq!(view.options, None, "case: {case_name} failed");
+            assert_eq!(view.back, None, "case: {case_name} failed");
+
+            let Event::Question { text, .. } = session.handle(Some("")).unwrap() else {
+                panic!("case: {case_name} failed");
+            };
+            let expected = format!("Answer: {back}\nExplanation: A disguise\n\nHow well");
+            assert!(text.contains(&expected), "case: {case_name} failed");
+            assert_eq!(
+                session.view().unwrap().back,
+                Some(format!("{back}\n\nA disguise")),
+                "case: {case_name} failed"
+            );
+
+            let Event::Results { text } = session.handle(Some("4")).unwrap() else {
+                panic!("case: {case_name} failed");
+            };
+            let expected = format!(
+                "1. [Easy] {front}\n   Correct answer: {back}\n   Explanation:    A disguise"
+            );
+            assert!(text.contains(&expected), "case: {case_name} failed");
+            let recall: Vec<u8> = session.recall().into_iter().map(|(_, x)| x).collect();
+            assert_eq!(recall, vec![5], "case: {case_name} failed");
+            let outcomes = session.outcomes();
+            assert_eq!(outcomes[0].status, history::Status::Correct);
+        }
+    }
+
+    #[test]
+    fn test_session_feedback() {
+        let mut session = session(true);
+        session.start().unwrap();
+
+        let Event::Feedback { text } = session.handle(Some("False")).unwrap() else {
+            panic!("expected feedback");
+        };
+        assert!(text.contains("Correct answer: True"));
+        assert!(text.contains("Why not:        7 8 (ate) 9"));
+        assert!(!session.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_input_read_line() {
+        let mut input = "a\r\nb\n\nc".as_bytes();
+        let mut lines = Vec::new();
+        while let Some(line) = Input::read_line(&mut input).await.unwrap() {
+            lines.push(line);
+        }
+        assert_eq!(lines, vec!["a", "b", "", "c"]);
+
+        let mut input = BackgroundInput::new(Box::new("a\nb".as_bytes()));
+        assert_eq!(input.read_line().await.unwrap(), Some("a".to_string()));
+        assert_eq!(input.read_line().await.unwrap(), Some("b".to_string()));
+        assert_eq!(input.read_line().await.unwrap(), None);
+    }
+
+    /// Input from someone who does not answer, it runs out after `0`.
+    struct Silence(Duration);
+
+    impl Input for Silence {
+        async fn read_line(&mut self) -> std::io::Result<Option<String>> {
+            tokio::time::sleep(self.0).await;
+            Ok(None)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_time_limits() {
+        let millis = Duration::from_millis;
+        let cases = vec![
+            (
+                "quiz",
+                TimeLimits {
+                    quiz: Some(millis(20)),
+                    question: None,
+                },
+                vec!["question 0", "Time is up!"],
+            ),
+            (
+                "question",
+                TimeLimits {
+                    quiz: None,
+                    question: Some(millis(20)),
+                },
+                vec![
+                    "question 0",
+                    "question 1: Time is up for question 1",
+                    "review: Time is up for question 2",
+                    "Results: 0/2 (0%)",
+                ],
+            ),
+            (
+                "question runs out after the quiz",
+                TimeLimits {
+                    quiz: Some(millis(30)),
+                    question: Some(millis(20)),
+                },
+                vec![
+                    "question 0",
+                    "question 1: Time is up for question 1",
+                    "Time is up!",
+                ],
+            ),
+        ];
+
+        for (case_name, limits, expected) in cases {
+            let session = session(false).with_time_limits(limits);
+            assert_eq!(
+                run_with(session, &mut Silence(millis(200))).await,
+                expected,
+                "case: {case_name} failed"
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_huge_time_limits() {
+        let mut session = session(false).with_time_limits(TimeLimits {
+            quiz: Some(Duration::from_secs(u64::MAX)),
+            question: Some(Duration::from_secs(u64::MAX)),
+        });
+        session.start().unwrap();
+        assert_eq!(session.deadlines(), Deadlines::default());
+    }
+
+    #[test]
+    fn test_session_time_spent() {
+        let mut session = session(false).with_time_limits(TimeLimits {
+            quiz: None,
+            question: Some(Duration::from_millis(10)),
+        });
+        session.start().unwrap();
+        assert!(session.deadlines().question.is_some());
+        std::thread::sleep(Duration::from_millis(15));
+
+        let Event::Question { notice, .. } = session.handle(Some("True")).unwrap() else {
+            panic!("expected a question");
+        };
+        assert_eq!(notice, Some("The time for question 1 is up".to_string()));
+        assert_eq!(session.deadlines(), Deadlines::default());
+        assert!(session.progress().spent[&0] >= 15);
+
+        let Event::Results { text } = session.handle(Some(":quit")).unwrap() else {
+            panic!("expected the results");
+        };
+        assert!(text.contains("Time:           00:00"));
+    }
+
+    #[test]
+    fn test_deadlines() {
+        let now = Instant::now();
+        let quiz = Some(now + Duration::from_millis(249_500));
+        let question = Some(now + Duration::from_secs(25));
+        let cases = vec![
+            ("none", None, None, None, None),
+            ("quiz", quiz, None, quiz, Some("04:10 left")),
+            (
+                "question",
+                None,
+                question,
+                question,
+                Some("00:25 left for this question"),
+            ),
+            (
+                "both",
+                quiz,
+                question,
+                question,
+                Some("04:10 left, 00:25 for this question"),
+            ),
+        ];
+
+        for (case_name, quiz, question, next, describe) in cases {
+            let deadlines = Deadlines { quiz, question };
+            assert_eq!(deadlines.next(), next, "case: {case_name} failed");
+            assert_eq!(
+                deadlines.describe(now),
+                describe.map(String::from),
+                "case: {case_name} failed"
+            );
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::requests;
+
+/// Where a JSON document (a quiz or a schema) is loaded from.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Source {
+    Path(PathBuf),
+    Url(String),
+    Stdin,
+}
+
+impl Source {
+    pub fn parse(data: &str) -> Result<Self, String> {
+        match data {
+            "" => Err("an empty string is not a valid source".to_string()),
+            "-" => Ok(Self::Stdin),
+            x if x.starts_with("http://") || x.starts_with("https://") => {
+                Ok(Self::Url(x.to_string()))
+            }
+            x => Ok(Self::Path(PathBuf::from(x))),
+        }
+    }
+
+    pub async fn load(&self) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&self.read().await?)?)
+    }
+
+    /// The document as it is written, to point at the lines of its problems.
+    pub async fn read(&self) -> Result<String, Box<dyn std::error::Error>> {
+        match self {
+            Self::Path(path) => Ok(tokio::fs::read_to_string(path)
+                .await
+                .map_err(|e| format!("Unable to read {}: {e}", path.display()))?),
+            Self::Url(url) => requests::get_text(url).await,
+            Self::Stdin => {
+                let mut text = String::new();
+                std::io::stdin().read_to_string(&mut text)?;
+                Ok(text)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Path(path) => write!(f, "{}", path.display()),
+            Self::Url(url) => write!(f, "{url}"),
+            Self::Stdin => write!(f, "<stdin>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_parse() {
+        let cases = vec![
+            ("-", Ok(Source::Stdin)),
+            (
+                "https://example.com/quiz.json",
+                Ok(Source::Url("https://example.com/quiz.json".to_string())),
+            ),
+            (
+                "http://example.com/quiz.json",
+                Ok(Source::Url("http://example.com/quiz.json".to_string())),
+            ),
+            (
+                "quizzes/quiz.json",
+                Ok(Source::Path(PathBuf::from("quizzes/quiz.json"))),
+            ),
+            ("", Err("an empty string is not a valid source".to_string())),
+        ];
+
+        for (data, expected) in cases {
+            assert_eq!(Source::parse(data), expected, "case: '{data}' failed");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_source_load_path() {
+        let path = std::env::temp_dir().join("terminal_quizlit_source_test.json");
+        std::fs::write(&path, r#"{"questions": []}"#).unwrap();
+
+        let json = Source::Path(path.clone()).load().await.unwrap();
+        assert_eq!(json, serde_json::json!({"questions": []}));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(Source::Path(path).load().await.is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::quizlit::{Grade, GradeStatus};
+use crate::save;
+
+/// Ease factor new cards start with, as in SM-2.
+const INITIAL_EASE: f32 = 2.5;
+/// SM-2 never lets the ease factor drop below this.
+const MINIMUM_EASE: f32 = 1.3;
+
+/// One review of a card.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Review {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    /// How well the question was answered, from 0 (not at all) to 5 (perfectly).
+    pub quality: u8,
+}
+
+/// The SM-2 schedule of a single question.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Card {
+    /// Reviews in a row that were answered well enough.
+    pub repetitions: u32,
+    /// Days until the next review.
+    pub interval: u64,
+    pub ease: f32,
+    /// Day the card is due, in days since the Unix epoch.
+    pub due: u64,
+    pub history: Vec<Review>,
+}
+
+impl Default for Card {
+    fn default() -> Self {
+        Self {
+            repetitions: 0,
+            interval: 0,
+            ease: INITIAL_EASE,
+            due: 0,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Card {
+    pub fn is_due(&self, today: u64) -> bool {
+        self.due <= today
+    }
+
+    /// Schedules the next review after answering with `quality` on `today`.
+    pub fn review(&mut self, quality: u8, today: u64) {
+        let quality = quality.min(5);
+        if quality >= 3 {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f32 * self.ease).round() as u64,
+            };
+            self.repetitions += 1;
+        } else {
+            self.repetitions = 0;
+            self.interval = 1;
+        }
+
+        let miss = f32::from(5 - quality);
+        self.ease = (self.ease + 0.1 - miss * (0.08 + miss * 0.02)).max(MINIMUM_EASE);
+        self.due = today + self.interval;
+        self.history.push(Review {
+            day: today,
+            quality,
+        });
+    }
+}
+
+/// How well a grade counts as remembering the answer, on the SM-2 scale.
+pub fn quality(grade: &Grade) -> u8 {
+    match grade.status {
+        // A note on a correct answer means a typo was forgiven.
+        GradeStatus::Correct if grade.note.is_some() => 4,
+        GradeStatus::Correct => 5,
+        GradeStatus::Partial if grade.score >= 0.5 => 3,
+        GradeStatus::Partial => 2,
+        // A note on an incorrect answer means it was close.
+        GradeStatus::Incorrect if grade.note.is_some() => 1,
+        GradeStatus::Incorrect => 0,
+    }
+}
+
+/// The cards of every question of a quiz that was studied.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Deck {
+    /// Where the quiz is loaded from, an absolute path or a URL.
+    pub source: String,
+    /// Cards by the text of their question, so that they survive questions
+    /// being added, removed or moved around.
+    pub cards: BTreeMap<String, Card>,
+}
+
+impl Deck {
+    pub fn new(source: String) -> Self {
+        Self {
+            source,
+            cards: BTreeMap::new(),
+        }
+    }
+
+    /// Whether `question` should be asked on `today`, questions that were
+    /// never studied always are.
+    pub fn is_due(&self, question: &str, today: u64) -> bool {
+        self.cards.get(question).is_none_or(|x| x.is_due(today))
+    }
+
+    /// Updates the schedule of `question` with how well it was remembered,
+    /// see `quality`.
+    pub fn record(&mut self, question: &str, quality: u8, today: u64) {
+        self.cards
+            .entry(question.to_string())
+            .or_default()
+            .review(quality, today);
+    }
+
+    /// e.g. "2 due now, 10 studied, next due in 3 days" for the quiz with
+    /// `questions`. Like in `is_due`, questions never studied are due.
+    pub fn summary(&self, questions: &[String], today: u64) -> String {
+        let due = questions.iter().filter(|x| self.is_due(x, today)).count();
+        let cards: Vec<&Card> = questions.iter().filter_map(|x| self.cards.get(x)).collect();
+        let mut summary = format!("{due} due now, {} studied", cards.len());
+
+        let next = cards
+            .iter()
+            .filter(|x| !x.is_due(today))
+            .map(|x| x.due)
+            .min();
+        if let Some(next) = next {
+            summary.push_str(&format!(", next due {}", in_days(next - today)));
+        }
+
+        summary
+    }
+}
+
+/// e.g. "tomorrow" or "in 3 days".
+pub fn in_days(days: u64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        _ => format!("in {days} days"),
+    }
+}
+
+/// Days since the Unix epoch.
+pub fn today() -> u64 {
+    save::now() / 86400
+}
+
+/// Where the decks are kept.
+pub fn decks_dir() -> Option<PathBuf> {
+    save::data_dir().map(|x| x.join("study"))
+}
+
+fn deck_path(dir: &Path, source: &str) -> PathBuf {
+    dir.join(format!("{:016x}.json", save::fnv1a(source.as_bytes())))
+}
+
+/// The deck of the quiz loaded from `source`, a new one when it was never studied.
+pub fn load(dir: &Path, source: &str) -> Result<Deck, Box<dyn std::error::Error>> {
+    match std::fs::read_to_string(deck_path(dir, source)) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            Ok(Deck::new(source.to_string()))
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub fn write(dir: &Path, deck: &Deck) -> std::io::Result<()> {
+    save::write_json(&deck_path(dir, &deck.source), deck)
+}
+
+/// Every deck in `dir`, sorted by where their quiz is loaded from. Files that
+/// cannot be read are left out.
+pub fn list(dir: &Path) -> std::io::Result<Vec<Deck>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error),
+    };
+
+    let mut decks = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_none_or(|x| x != "json") {
+            continue;
+        }
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(deck) = serde_json::from_str::<Deck>(&text) {
+            decks.push(deck);
+        }
+    }
+
+    decks.sort_by(|a, b| a.source.cmp(&b.source));
+    Ok(decks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grade(status: GradeStatus, score: f32, note: Option<&str>) -> Grade {
+        Grade {
+            status,
+            score,
+            expected: vec![],
+            explanations: vec![],
+            incorrect_explanations: vec![],
+            note: note.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_quality() {
+        let cases = vec![
+            ("correct", grade(GradeStatus::Correct, 1.0, None), 5),
+            ("typo", grade(GradeStatus::Correct, 1.0, Some("typo")), 4),
+            ("mostly right", grade(GradeStatus::Partial, 0.5, None), 3),
+            ("mostly wrong", grade(GradeStatus::Partial, 0.25, None), 2),
+            (
+                "close",
+                grade(GradeStatus::Incorrect, 0.0, Some("close")),
+                1,
+            ),
+            ("incorrect", grade(GradeStatus::Incorrect, 0.0, None), 0),
+        ];
+
+        for (case_name, grade, expected) in cases {
+            assert_eq!(quality(&grade), expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_card_review() {
+        // The interval, repetitions and ease after each review.
+        let cases = vec![
+            (
+                "remembered every time",
+                vec![5, 5, 5],
+                vec![(1, 1, 2.6), (6, 2, 2.7), (16, 3, 2.8)],
+            ),
+            (
+                "forgotten",
+                vec![4, 4, 1, 4],
+                vec![(1, 1, 2.5), (6, 2, 2.5), (1, 0, 1.96), (1, 1, 1.96)],
+            ),
+            (
+                "ease bottoms out",
+                vec![0, 0, 0],
+                vec![(1, 0, 1.7), (1, 0, 1.3), (1, 0, 1.3)],
+            ),
+        ];
+
+        for (case_name, qualities, expected) in cases {
+            let reviews = qualities.len();
+            let mut card = Card::default();
+            assert!(card.is_due(0), "case: {case_name} failed");
+
+            for (day, (quality, (interval, repetitions, ease))) in
+                qualities.into_iter().zip(expected).enumerate()
+            {
+                let today = 100 + day as u64;
+                card.review(quality, today);
+                assert_eq!(card.interval, interval, "case: {case_name} failed");
+                assert_eq!(card.repetitions, repetitions, "case: {case_name} failed");
+                assert!((card.ease - ease).abs() < 0.001, "case: {case_name} failed");
+                assert_eq!(card.due, today + interval, "case: {case_name} failed");
+                assert!(!card.is_due(today), "case: {case_name} failed");
+            }
+            assert_eq!(card.history.len(), reviews, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_deck() {
+        let mut deck = Deck::new("quiz.json".to_string());
+        let questions = ["Question?", "Other?", "New?"].map(String::from);
+        assert!(deck.is_due("Question?", 100));
+        assert_eq!(deck.summary(&questions, 100), "3 due now, 0 studied");
+
+        deck.record("Question?", 5, 100);
+        deck.record("Other?", 5, 100);
+        deck.record("Other?", 5, 101);
+        deck.record("Removed?", 5, 90);
+        assert!(!deck.is_due("Question?", 100));
+        assert!(deck.is_due("Question?", 101));
+        assert!(deck.is_due("New?", 100));
+        assert_eq!(
+            deck.summary(&questions, 100),
+            "1 due now, 2 studied, next due tomorrow"
+        );
+        assert_eq!(
+            deck.summary(&questions, 101),
+            "2 due now, 2 studied, next due in 6 days"
+        );
+    }
+
+    #[test]
+    fn test_load_and_write() {
+        let dir =
+            std::env::temp_dir().join(format!("terminal_quizlit-study-{}", std::process::id()));
+        assert_eq!(list(&dir).unwrap(), vec![]);
+
+        let mut deck = load(&dir, "/quizzes/b.json").unwrap();
+        assert_eq!(deck, Deck::new("/quizzes/b.json".to_string()));
+        deck.record("Question?", 5, 100);
+        write(&dir, &deck).unwrap();
+        write(&dir, &Deck::new("/quizzes/a.json".to_string())).unwrap();
+
+        assert_eq!(load(&dir, "/quizzes/b.json").unwrap(), deck);
+        let sources: Vec<String> = list(&dir).unwrap().into_iter().map(|x| x.source).collect();
+        assert_eq!(sources, vec!["/quizzes/a.json", "/quizzes/b.json"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
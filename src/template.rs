@@ -1,5 +1,10 @@
 use askama::Template;
 
+use crate::flashcard;
+use crate::history;
+use crate::quizlit;
+use crate::save;
+
 #[allow(dead_code)]
 #[derive(Template)]
 #[template(path = "true_false_question.txt")]
@@ -26,18 +31,24 @@ impl<'a> TrueFalseQuestionTemplate<'a> {
 pub struct SelectionQuestionTemplate<'a> {
     question: &'a str,
     answers: Vec<String>,
+    select_all: bool,
 }
 
 #[allow(dead_code)]
 impl<'a> SelectionQuestionTemplate<'a> {
-    pub fn new(question: &'a str, answers: Vec<&'a str>) -> Self {
+    /// `select_all` asks the user to pick every correct answer.
+    pub fn new(question: &'a str, answers: Vec<&'a str>, select_all: bool) -> Self {
         let answers: Vec<String> = answers
             .iter()
-            .zip("abcdefghijklmnop".chars())
-            .map(|(v, i)| format!("{i}. {v}"))
+            .enumerate()
+            .map(|(i, v)| format!("{}. {v}", quizlit::choice_label(i)))
             .collect();
 
-        Self { question, answers }
+        Self {
+            question,
+            answers,
+            select_all,
+        }
     }
 }
 
@@ -68,14 +79,210 @@ impl<'a> OrderQuestionTemplate<'a> {
     pub fn new(question: &'a str, answers: Vec<&'a str>) -> Self {
         let answers: Vec<String> = answers
             .iter()
-            .zip("abcdefghijklmnop".chars())
-            .map(|(v, i)| format!("{i}. {v}"))
+            .enumerate()
+            .map(|(i, v)| format!("{}. {v}", quizlit::choice_label(i)))
             .collect();
 
         Self { question, answers }
     }
 }
 
+/// A flashcard, with its answer once it is revealed.
+#[derive(Template)]
+#[template(path = "flashcard.txt")]
+pub struct FlashcardTemplate<'a> {
+    front: &'a str,
+    back: &'a [String],
+    explanations: &'a [String],
+    revealed: bool,
+}
+
+impl<'a> FlashcardTemplate<'a> {
+    pub fn new(card: &'a flashcard::Card, revealed: bool) -> Self {
+        Self {
+            front: &card.front,
+            back: &card.back,
+            explanations: &card.explanations,
+            revealed,
+        }
+    }
+}
+
+/// A single row of the results report.
+pub struct QuestionResult {
+    pub number: usize,
+    pub question: String,
+    pub status: String,
+    pub answer: Option<String>,
+    pub expected: Vec<String>,
+    pub explanations: Vec<String>,
+    pub incorrect_explanations: Vec<String>,
+    pub note: Option<String>,
+    /// Time spent on the question, e.g. "01:05".
+    pub time: Option<String>,
+    /// A flashcard graded by the user, there is no answer to show.
+    pub self_graded: bool,
+}
+
+/// Shown right after a question is answered in practice mode.
+#[derive(Template)]
+#[template(path = "feedback.txt")]
+pub struct FeedbackTemplate {
+    result: QuestionResult,
+}
+
+impl FeedbackTemplate {
+    pub fn new(result: QuestionResult) -> Self {
+        Self { result }
+    }
+}
+
+/// A question on the review screen, with the answer given so far.
+pub struct ReviewRow {
+    pub number: usize,
+    pub question: String,
+    pub answer: Option<String>,
+    pub flagged: bool,
+}
+
+/// Every question of the quiz, shown before the answers are submitted.
+#[derive(Template)]
+#[template(path = "review.txt")]
+pub struct ReviewTemplate {
+    answered: usize,
+    flagged: usize,
+    rows: Vec<ReviewRow>,
+}
+
+impl ReviewTemplate {
+    pub fn new(rows: Vec<ReviewRow>) -> Self {
+        Self {
+            answered: rows.iter().filter(|x| x.answer.is_some()).count(),
+            flagged: rows.iter().filter(|x| x.flagged).count(),
+            rows,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "results.txt")]
+pub struct ResultsTemplate {
+    score: String,
+    seed: u64,
+    total: usize,
+    percentage: String,
+    results: Vec<QuestionResult>,
+    round: usize,
+}
+
+impl ResultsTemplate {
+    pub fn new(score: f32, seed: u64, results: Vec<QuestionResult>) -> Self {
+        let total = results.len();
+        let percentage = match total {
+            0 => 0.0,
+            _ => score / total as f32 * 100.0,
+        };
+
+        Self {
+            score: format_number(score),
+            seed,
+            total,
+            percentage: format_number(percentage),
+            results,
+            round: 1,
+        }
+    }
+
+    /// Results of a retry of the missed questions, which have no seed to
+    /// take them again with.
+    pub fn with_round(mut self, round: usize) -> Self {
+        self.round = round;
+        self
+    }
+}
+
+/// How many of the latest attempts the trend of `StatsTemplate` shows.
+const TREND: usize = 10;
+
+/// How the attempts at a quiz went.
+#[derive(Template)]
+#[template(path = "stats.txt")]
+pub struct StatsTemplate {
+    quiz: String,
+    attempts: usize,
+    latest_ago: String,
+    best: String,
+    latest: String,
+    average: String,
+    trend: Option<String>,
+    most_missed: Vec<String>,
+}
+
+impl StatsTemplate {
+    /// `now` is in seconds since the Unix epoch.
+    pub fn new(stats: &history::QuizStats, now: u64) -> Self {
+        let score = |x: &history::Attempt| {
+            format!(
+                "{}/{} ({}%), attempt {}",
+                format_number(x.score),
+                x.total,
+                format_number(x.percentage()),
+                stats.number(x)
+            )
+        };
+
+        let trend = match stats.attempts.len() {
+            1 => None,
+            count => {
+                let percentages: Vec<String> = stats.attempts[count.saturating_sub(TREND)..]
+                    .iter()
+                    .map(|x| format!("{}%", format_number(x.percentage())))
+                    .collect();
+                let improvement = stats.improvement();
+                let change = match improvement {
+                    x if x > 0.0 => format!("up {} points", format_number(x)),
+                    x if x < 0.0 => format!("down {} points", format_number(-x)),
+                    _ => "no change".to_string(),
+                };
+                Some(format!(
+                    "{}, {change} since the first attempt",
+                    percentages.join(" → ")
+                ))
+            }
+        };
+
+        let most_missed = stats
+            .most_missed
+            .iter()
+            .map(|x| format!("{} (missed {}/{})", x.question, x.misses, x.asked))
+            .collect();
+
+        Self {
+            quiz: stats.quiz.to_string(),
+            attempts: stats.attempts.len(),
+            latest_ago: save::ago(now.saturating_sub(stats.latest.finished_at)),
+            best: score(stats.best),
+            latest: score(stats.latest),
+            average: format_number(stats.average),
+            trend,
+            most_missed,
+        }
+    }
+}
+
+/// Formats a number of seconds as minutes and seconds, e.g. "01:05".
+pub fn format_clock(seconds: u64) -> String {
+    format!("{:02}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Formats a number without a fractional part when it is whole, e.g. 3 and 2.5.
+fn format_number(number: f32) -> String {
+    match number.fract() == 0.0 {
+        true => format!("{number:.0}"),
+        false => format!("{number:.1}"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,7 +294,9 @@ mod tests {
 a. answer_1
 b. answer_2
 c. answer_3
-d. answer_4"#;
+d. answer_4
+
+Put the items in the correct order, e.g. "b a c" or "2,1,3"."#;
 
         let question = OrderQuestionTemplate::new(
             "question?",
@@ -122,11 +331,296 @@ d. answer_4"#;
         let question = SelectionQuestionTemplate::new(
             "question?",
             vec!["answer_1", "answer_2", "answer_3", "answer_4"],
+            false,
         );
         let text = question.render().unwrap();
         for (line, wanted_line) in text.lines().zip(expected.lines()) {
             assert!(line.contains(wanted_line));
         }
+        assert!(!text.contains("Select all that apply"));
+    }
+
+    #[test]
+    fn test_selection_question_select_all() {
+        let expected = r#"Question: question?
+
+a. answer_1
+b. answer_2
+
+Select all that apply, e.g. "a c"."#;
+
+        let question =
+            SelectionQuestionTemplate::new("question?", vec!["answer_1", "answer_2"], true);
+        let text = question.render().unwrap();
+        for (line, wanted_line) in text.lines().zip(expected.lines()) {
+            assert!(line.contains(wanted_line));
+        }
+        assert!(text.contains("Select all that apply"));
+    }
+
+    #[test]
+    fn test_question_many_answers() {
+        let answers: Vec<String> = (1..=30).map(|x| format!("answer_{x}")).collect();
+        let answers: Vec<&str> = answers.iter().map(String::as_str).collect();
+
+        let selection = SelectionQuestionTemplate::new("question?", answers.clone(), false)
+            .render()
+            .unwrap();
+        let order = OrderQuestionTemplate::new("question?", answers)
+            .render()
+            .unwrap();
+        for text in [selection, order] {
+            assert!(text.contains("p. answer_16"), "{text}");
+            assert!(text.contains("q. answer_17"), "{text}");
+            assert!(text.contains("z. answer_26"), "{text}");
+            assert!(text.contains("27. answer_27"), "{text}");
+            assert!(text.contains("30. answer_30"), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_results() {
+        let expected = r#"Results: 1.5/4 (37.5%)
+Seed: 42 (use --seed 42 to get the same layout again)
+
+1. [Correct] question 1?
+   Your answer:    a. answer_1
+   Correct answer: a. answer_1
+   Explanation:    because
+   Time:           00:42
+
+2. [Partial] question 2?
+   Your answer:    a, b
+   Correct answer: a, b, c
+
+3. [Incorrect] question 3?
+   Your answer:    Lucky
+   Correct answer: Lucy
+   Note:           close — did you mean Lucy?
+
+4. [Unanswered] question 4?
+   Your answer:    (unanswered)
+   Correct answer: answer_3
+"#;
+
+        let results = vec![
+            QuestionResult {
+                number: 1,
+                question: "question 1?".to_string(),
+                status: "Correct".to_string(),
+                answer: Some("a. answer_1".to_string()),
+                expected: vec!["a. answer_1".to_string()],
+                explanations: vec!["because".to_string()],
+                incorrect_explanations: vec![],
+                note: None,
+                time: Some("00:42".to_string()),
+                self_graded: false,
+            },
+            QuestionResult {
+                number: 2,
+                question: "question 2?".to_string(),
+                status: "Partial".to_string(),
+                answer: Some("a, b".to_string()),
+                expected: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                explanations: vec![],
+                incorrect_explanations: vec![],
+                note: None,
+                time: None,
+                self_graded: false,
+            },
+            QuestionResult {
+                number: 3,
+                question: "question 3?".to_string(),
+                status: "Incorrect".to_string(),
+                answer: Some("Lucky".to_string()),
+                expected: vec!["Lucy".to_string()],
+                explanations: vec![],
+                incorrect_explanations: vec![],
+                note: Some("close — did you mean Lucy?".to_string()),
+                time: None,
+                self_graded: false,
+            },
+            QuestionResult {
+                number: 4,
+                question: "question 4?".to_string(),
+                status: "Unanswered".to_string(),
+                answer: None,
+                expected: vec!["answer_3".to_string()],
+                explanations: vec![],
+                incorrect_explanations: vec![],
+                note: None,
+                time: None,
+                self_graded: false,
+            },
+        ];
+
+        let text = ResultsTemplate::new(1.5, 42, results).render().unwrap();
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_results_round() {
+        let results = vec![QuestionResult {
+            number: 1,
+            question: "question 1?".to_string(),
+            status: "Correct".to_string(),
+            answer: Some("Lucy".to_string()),
+            expected: vec!["Lucy".to_string()],
+            explanations: vec![],
+            incorrect_explanations: vec![],
+            note: None,
+            time: None,
+            self_graded: false,
+        }];
+
+        let text = ResultsTemplate::new(1.0, 42, results)
+            .with_round(2)
+            .render()
+            .unwrap();
+        assert_eq!(
+            text,
+            "Round 2 results: 1/1 (100%)\n\n1. [Correct] question 1?\n   Your answer:    Lucy\n   Correct answer: Lucy\n"
+        );
+    }
+
+    #[test]
+    fn test_feedback() {
+        let expected = r#"2. [Incorrect] Is the number 9 is afraid of the number 7?
+   Your answer:    False
+   Correct answer: True
+   Explanation:    Because 7 8 (ate) 9
+   Why not:        Because 7 is not a predator
+
+Press enter to continue"#;
+
+        let result = QuestionResult {
+            number: 2,
+            question: "Is the number 9 is afraid of the number 7?".to_string(),
+            status: "Incorrect".to_string(),
+            answer: Some("False".to_string()),
+            expected: vec!["True".to_string()],
+            explanations: vec!["Because 7 8 (ate) 9".to_string()],
+            incorrect_explanations: vec!["Because 7 is not a predator".to_string()],
+            note: None,
+            time: None,
+            self_graded: false,
+        };
+
+        let text = FeedbackTemplate::new(result).render().unwrap();
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_review() {
+        let expected = r#"Review: 1/2 answered, 1 flagged
+
+1. Is the number 9 is afraid of the number 7?
+   Your answer:    False
+2. [Flagged] What devil fruit did Luffy eat?
+   Your answer:    (unanswered)
+
+Type a question number to change its answer, :submit to lock in your answers and
+see the results, or press enter to go back.
+
+>>> "#;
+
+        let rows = vec![
+            ReviewRow {
+                number: 1,
+                question: "Is the number 9 is afraid of the number 7?".to_string(),
+                answer: Some("False".to_string()),
+                flagged: false,
+            },
+            ReviewRow {
+                number: 2,
+                question: "What devil fruit did Luffy eat?".to_string(),
+                answer: None,
+                flagged: true,
+            },
+        ];
+
+        let text = ReviewTemplate::new(rows).render().unwrap();
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_stats() {
+        let attempt = |finished_at, statuses: Vec<(&str, history::Status, f32)>| {
+            let questions = statuses
+                .into_iter()
+                .map(|(question, status, score)| history::Outcome {
+                    question: question.to_string(),
+                    status,
+                    score,
+                    time: 5,
+                })
+                .collect();
+            history::Attempt::new("/quizzes/quiz.json".to_string(), finished_at, 20, questions)
+        };
+        let attempts = [
+            attempt(
+                100,
+                vec![
+                    ("Is 9 afraid of 7?", history::Status::Correct, 1.0),
+                    ("Which fruit?", history::Status::Incorrect, 0.0),
+                ],
+            ),
+            attempt(
+                200,
+                vec![
+                    ("Is 9 afraid of 7?", history::Status::Correct, 1.0),
+                    ("Which fruit?", history::Status::Partial, 0.5),
+                ],
+            ),
+        ];
+
+        let cases = vec![
+            (
+                "one attempt",
+                &attempts[..1],
+                r#"/quizzes/quiz.json
+   Attempts:    1, the latest 5 minutes ago
+   Best:        1/2 (50%), attempt 1
+   Latest:      1/2 (50%), attempt 1
+   Average:     50%
+   Most missed: Which fruit? (missed 1/1)"#,
+            ),
+            (
+                "improving",
+                &attempts[..],
+                r#"/quizzes/quiz.json
+   Attempts:    2, the latest 3 minutes ago
+   Best:        1.5/2 (75%), attempt 2
+   Latest:      1.5/2 (75%), attempt 2
+   Average:     62.5%
+   Trend:       50% → 75%, up 25 points since the first attempt
+   Most missed: Which fruit? (missed 2/2)"#,
+            ),
+        ];
+
+        for (case_name, attempts, expected) in cases {
+            let stats = history::stats(attempts);
+            let text = StatsTemplate::new(&stats[0], 400).render().unwrap();
+            assert_eq!(text, expected, "case: {case_name} failed");
+        }
+    }
+
+    #[test]
+    fn test_format_clock() {
+        let cases = vec![(0, "00:00"), (65, "01:05"), (3600, "60:00")];
+
+        for (seconds, expected) in cases {
+            assert_eq!(format_clock(seconds), expected, "case: {seconds} failed");
+        }
+    }
+
+    #[test]
+    fn test_format_number() {
+        let cases = vec![(3.0, "3"), (2.5, "2.5"), (66.666, "66.7"), (0.0, "0")];
+
+        for (number, expected) in cases {
+            assert_eq!(format_number(number), expected, "case: {number} failed");
+        }
     }
 
     #[test]
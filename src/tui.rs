@@ -0,0 +1,679 @@
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::quizlit::{choice_label, QuestionType};
+use crate::session::{Deadlines, Event, QuestionView, QuizSession};
+use crate::template::format_clock;
+
+const HELP: &str = "\
+Up/Down, k/j      Move through the options
+Space             Pick an option, pick again to unpick it
+Enter             Answer with the picked options, or the highlighted one
+Left/Right        Previous/next question
+n, p              Next/previous question
+s                 Go to the next unanswered question
+f                 Flag the question to come back to it later, or unflag it
+l                 List the questions and which are answered or flagged
+h                 Give a hint for the question
+r, F2             Review the answers before submitting them
+Enter, Space      Show the answer of a flashcard
+1, 2, 3, 4        Grade a flashcard: again, hard, good or easy
+?, F1             Show or hide this help
+q, Esc            Quit and show the results
+w, Ctrl-S         Save and exit, to resume the session later
+
+Order questions are answered by picking the items in the correct order.
+Free text questions take the typed text, use Esc to quit, Ctrl-S to save and
+F1 for help.";
+
+/// What a key press asks for.
+#[derive(Debug, PartialEq)]
+enum Action {
+    Nothing,
+    /// A line for the session, a command or an answer.
+    Send(String),
+    ToggleHelp,
+}
+
+/// What has been picked on the current question so far.
+#[derive(Debug, Default, PartialEq)]
+struct Picker {
+    highlighted: usize,
+    /// Picked options in the order they were picked.
+    picked: Vec<usize>,
+    /// Text typed for free text questions.
+    text: String,
+}
+
+impl Picker {
+    fn key(&mut self, view: &QuestionView, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('c') => return Action::Send(":quit".to_string()),
+                KeyCode::Char('s') => return Action::Send(":save".to_string()),
+                _ => {}
+            }
+        }
+
+        let count = view.options.as_ref().map_or(0, Vec::len);
+        let free_text = view.kind == QuestionType::UserInput && !view.flashcard;
+        let revealed = view.back.is_some();
+        match key.code {
+            KeyCode::Esc => Action::Send(":quit".to_string()),
+            KeyCode::F(1) => Action::ToggleHelp,
+            KeyCode::F(2) => Action::Send(":review".to_string()),
+            KeyCode::Left | KeyCode::PageUp => Action::Send(":previous".to_string()),
+            KeyCode::Right | KeyCode::PageDown => Action::Send(":next".to_string()),
+            KeyCode::Enter if free_text => Action::Send(self.text.clone()),
+            KeyCode::Backspace if free_text => {
+                self.text.pop();
+                Action::Nothing
+            }
+            KeyCode::Char(c) if free_text => {
+                self.text.push(c);
+                Action::Nothing
+            }
+            KeyCode::Enter | KeyCode::Char(' ') if view.flashcard && !revealed => {
+                Action::Send(String::new())
+            }
+            KeyCode::Char(c @ '1'..='4') if view.flashcard && revealed => {
+                Action::Send(c.to_string())
+            }
+            KeyCode::Char('?') => Action::ToggleHelp,
+            KeyCode::Char('q') => Action::Send(":quit".to_string()),
+            KeyCode::Char('n') => Action::Send(":next".to_string()),
+            KeyCode::Char('p') => Action::Send(":previous".to_string()),
+            KeyCode::Char('s') => Action::Send(":skip".to_string()),
+            KeyCode::Char('f') => Action::Send(":flag".to_string()),
+            KeyCode::Char('l') => Action::Send(":list".to_string()),
+            KeyCode::Char('h') => Action::Send(":hint".to_string()),
+            KeyCode::Char('r') => Action::Send(":review".to_string()),
+            KeyCode::Char('w') => Action::Send(":save".to_string()),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.highlighted = self.highlighted.saturating_sub(1);
+                Action::Nothing
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.highlighted = (self.highlighted + 1).min(count.saturating_sub(1));
+                Action::Nothing
+            }
+            KeyCode::Char(' ') => {
+                self.toggle(view);
+                Action::Nothing
+            }
+            KeyCode::Enter => self.answer(view),
+            _ => Action::Nothing,
+        }
+    }
+
+    fn toggle(&mut self, view: &QuestionView) {
+        if let Some(index) = self.picked.iter().position(|x| *x == self.highlighted) {
+            self.picked.remove(index);
+            return;
+        }
+
+        let single = match view.kind {
+            QuestionType::Selection => !view.select_all,
+            QuestionType::Order => false,
+            _ => true,
+        };
+        if single {
+            self.picked.clear();
+        }
+        self.picked.push(self.highlighted);
+    }
+
+    /// The answer line for the picked options, or the highlighted one when nothing is picked.
+    fn answer(&self, view: &QuestionView) -> Action {
+        let Some(options) = &view.options else {
+            return Action::Nothing;
+        };
+        let picked = match self.picked.is_empty() {
+            true => vec![self.highlighted],
+            false => self.picked.clone(),
+        };
+
+        match view.kind {
+            // True or false questions are answered with the text of the option.
+            QuestionType::TrueFalse => Action::Send(options[picked[0]].clone()),
+            _ => Action::Send(
+                picked
+                    .into_iter()
+                    .map(choice_label)
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            ),
+        }
+    }
+
+    /// How an option is marked, "[x]" when picked or "[2]" for the second item of an order.
+    fn mark(&self, view: &QuestionView, index: usize) -> String {
+        match (self.picked.iter().position(|x| *x == index), &view.kind) {
+            (Some(position), QuestionType::Order) => format!("[{}]", position + 1),
+            (Some(_), _) => "[x]".to_string(),
+            (None, _) => "[ ]".to_string(),
+        }
+    }
+}
+
+/// The state of the screen between session events.
+struct App {
+    view: Option<QuestionView>,
+    deadlines: Deadlines,
+    picker: Picker,
+    notice: Option<String>,
+    feedback: Option<String>,
+    review: Option<Review>,
+    help: bool,
+    started: Instant,
+}
+
+/// The review screen, where a question number or a command is typed.
+#[derive(Debug, Default, PartialEq)]
+struct Review {
+    text: String,
+    typed: String,
+    scroll: u16,
+}
+
+impl Review {
+    fn key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => Action::Send(String::new()),
+            KeyCode::Enter => Action::Send(std::mem::take(&mut self.typed)),
+            KeyCode::Up => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::Nothing
+            }
+            KeyCode::Down => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::Nothing
+            }
+            KeyCode::Backspace => {
+                self.typed.pop();
+                Action::Nothing
+            }
+            KeyCode::Char(c) => {
+                self.typed.push(c);
+                Action::Nothing
+            }
+            _ => Action::Nothing,
+        }
+    }
+}
+
+impl App {
+    fn new() -> Self {
+        Self {
+            view: None,
+            deadlines: Deadlines::default(),
+            picker: Picker::default(),
+            notice: None,
+            feedback: None,
+            review: None,
+            help: false,
+            started: Instant::now(),
+        }
+    }
+
+    fn key(&mut self, key: KeyEvent) -> Action {
+        if self.help {
+            self.help = false;
+            return Action::Nothing;
+        }
+        if self.feedback.is_some() {
+            return Action::Send(String::new());
+        }
+        if let Some(review) = &mut self.review {
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                match key.code {
+                    KeyCode::Char('c') => return Action::Send(":quit".to_string()),
+                    KeyCode::Char('s') => return Action::Send(":save".to_string()),
+                    _ => {}
+                }
+            }
+            return review.key(key);
+        }
+
+        match &self.view {
+            Some(view) => self.picker.key(view, key),
+            None => Action::Nothing,
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        // Notices such as the list of questions may take several lines.
+        let notice_height = self.notice.as_ref().map_or(0, |x| x.lines().count() as u16);
+        let [main, notice, status] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(notice_height),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        match (&self.feedback, &self.review, &self.view) {
+            (Some(feedback), _, _) => frame.render_widget(
+                Paragraph::new(feedback.as_str())
+                    .wrap(Wrap { trim: false })
+                    .block(Block::bordered().title("Feedback")),
+                main,
+            ),
+            (None, Some(review), _) => {
+                // The prompt is drawn on the border so that it stays visible while scrolling.
+                let text = review.text.trim_end().trim_end_matches(">>>").trim_end();
+                frame.render_widget(
+                    Paragraph::new(text)
+                        .wrap(Wrap { trim: false })
+                        .scroll((review.scroll, 0))
+                        .block(
+                            Block::bordered()
+                                .title("Review (Up/Down to scroll, Esc to go back)")
+                                .title_bottom(format!("> {}_", review.typed)),
+                        ),
+                    main,
+                )
+            }
+            (None, None, Some(view)) => self.draw_question(frame, view, main),
+            (None, None, None) => {}
+        }
+
+        if let Some(text) = &self.notice {
+            let style = Style::default().add_modifier(Modifier::BOLD);
+            frame.render_widget(Paragraph::new(text.as_str()).style(style), notice);
+        }
+        frame.render_widget(
+            Paragraph::new(self.status()).style(Style::default().add_modifier(Modifier::REVERSED)),
+            status,
+        );
+
+        if self.help {
+            let area = centered(frame.area(), 80, 20);
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(HELP)
+                    .wrap(Wrap { trim: false })
+                    .block(Block::bordered().title("Help")),
+                area,
+            );
+        }
+    }
+
+    fn draw_question(&self, frame: &mut Frame, view: &QuestionView, area: Rect) {
+        let [question, answers] =
+            Layout::vertical([Constraint::Percentage(30), Constraint::Min(3)]).areas(area);
+
+        let title = match view.flashcard {
+            true => format!("Card {}/{}", view.position + 1, view.total),
+            false => format!("Question {}/{}", view.position + 1, view.total),
+        };
+        frame.render_widget(
+            Paragraph::new(view.question.as_str())
+                .wrap(Wrap { trim: true })
+                .block(Block::bordered().title(title)),
+            question,
+        );
+
+        if view.flashcard {
+            let (text, title) = match &view.back {
+                Some(back) => (back.as_str(), "Answer (1 again, 2 hard, 3 good, 4 easy)"),
+                None => ("Press Enter or Space to show the answer", "Answer"),
+            };
+            frame.render_widget(
+                Paragraph::new(text)
+                    .wrap(Wrap { trim: false })
+                    .block(Block::bordered().title(title)),
+                answers,
+            );
+            return;
+        }
+
+        let Some(options) = &view.options else {
+            let text = format!("> {}_", self.picker.text);
+            frame.render_widget(
+                Paragraph::new(text).block(Block::bordered().title("Your answer")),
+                answers,
+            );
+            return;
+        };
+
+        let title = match view.kind {
+            QuestionType::Order => "Pick the items in the correct order",
+            QuestionType::Selection if view.select_all => "Select all that apply",
+            _ => "Answers",
+        };
+        let items: Vec<ListItem> = options
+            .iter()
+            .enumerate()
+            .map(|(index, option)| {
+                let mark = self.picker.mark(view, index);
+                ListItem::new(format!("{mark} {}. {option}", choice_label(index)))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+
+        let mut state = ListState::default().with_selected(Some(self.picker.highlighted));
+        frame.render_stateful_widget(list, answers, &mut state);
+    }
+
+    fn status(&self) -> String {
+        let mut time = format_clock(self.started.elapsed().as_secs());
+        if let Some(left) = self.deadlines.describe(Instant::now()) {
+            time = format!("{time} | {left}");
+        }
+        match &self.view {
+            Some(view) => format!(
+                " {} {}/{}{} | {} {}/{} | {time} | ? help",
+                match view.flashcard {
+                    true => "Card",
+                    false => "Question",
+                },
+                view.position + 1,
+                view.total,
+                match view.flagged {
+                    true => " (flagged)",
+                    false => "",
+                },
+                match view.flashcard {
+                    true => "Graded",
+                    false => "Answered",
+                },
+                view.answered,
+                view.total
+            ),
+            None => format!(" {time} | ? help"),
+        }
+    }
+}
+
+/// A `width` by `height` area in the middle of `area`.
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Runs `session` full screen until it is over and returns the results report.
+pub fn run(session: &mut QuizSession) -> Result<String, Box<dyn std::error::Error>> {
+    let mut terminal = ratatui::init();
+    let result = run_app(&mut terminal, session);
+    ratatui::restore();
+    result
+}
+
+fn run_app(
+    terminal: &mut DefaultTerminal,
+    session: &mut QuizSession,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut app = App::new();
+    // The timer goes on from where a resumed session left off.
+    app.started = Instant::now()
+        .checked_sub(session.elapsed())
+        .unwrap_or(app.started);
+    let mut next = session.start()?;
+
+    loop {
+        match next {
+            Event::Results { text } | Event::Saved { text } => return Ok(text),
+            Event::Feedback { text } => app.feedback = Some(text),
+            Event::Review { notice, text, .. } => {
+                let scroll = app.review.as_ref().map_or(0, |x| x.scroll);
+                app.notice = notice;
+                app.review = Some(Review {
+                    text,
+                    typed: String::new(),
+                    scroll,
+                });
+            }
+            Event::Question {
+                position, notice, ..
+            } => {
+                // Picks are kept when an answer is rejected, they are cleared on a new question.
+                if app.view.as_ref().map(|x| x.position) != Some(position) {
+                    app.picker = Picker::default();
+                }
+                app.feedback = None;
+                app.review = None;
+                app.notice = notice;
+                app.view = session.view();
+            }
+        }
+        app.deadlines = session.deadlines();
+
+        next = loop {
+            terminal.draw(|frame| app.draw(frame))?;
+
+            let now = Instant::now();
+            if app.deadlines.next().is_some_and(|x| x <= now) {
+                break session.timeout()?;
+            }
+
+            // Wake up every now and then to keep the timer running, a resize
+            // is handled by simply drawing again.
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            let event::Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match app.key(key) {
+                Action::Send(line) => break session.handle(Some(&line))?,
+                Action::ToggleHelp => app.help = !app.help,
+                Action::Nothing => {}
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn view(kind: QuestionType, options: Option<Vec<&str>>, select_all: bool) -> QuestionView {
+        QuestionView {
+            position: 1,
+            total: 4,
+            answered: 1,
+            kind,
+            question: "What devil fruit did Luffy eat?".to_string(),
+            options: options.map(|x| x.into_iter().map(String::from).collect()),
+            select_all,
+            flagged: false,
+            flashcard: false,
+            back: None,
+        }
+    }
+
+    fn send(line: &str) -> Action {
+        Action::Send(line.to_string())
+    }
+
+    #[test]
+    fn test_picker_keys() {
+        let true_false = view(QuestionType::TrueFalse, Some(vec!["True", "False"]), false);
+        let selection = view(QuestionType::Selection, Some(vec!["a", "b", "c"]), false);
+        let select_all = view(QuestionType::Selection, Some(vec!["a", "b", "c"]), true);
+        let order = view(QuestionType::Order, Some(vec!["a", "b", "c"]), false);
+        let user_input = view(QuestionType::UserInput, None, false);
+        let card = QuestionView {
+            flashcard: true,
+            ..view(QuestionType::UserInput, None, false)
+        };
+        let revealed = QuestionView {
+            back: Some("Gum Gum Fruit".to_string()),
+            ..card.clone()
+        };
+
+        let cases = vec![
+            ("highlighted true_false", &true_false, "↓⏎", send("False")),
+            ("picked true_false", &true_false, "␣↓⏎", send("True")),
+            ("single selection", &selection, "␣↓␣⏎", send("b")),
+            ("stops at the last option", &selection, "↓↓↓↓⏎", send("c")),
+            ("select all", &select_all, "␣↓↓␣⏎", send("a c")),
+            ("unpick", &select_all, "␣↓␣␣⏎", send("a")),
+            ("order", &order, "↓↓␣↑↑␣↓␣⏎", send("c a b")),
+            ("navigation", &selection, "→", send(":next")),
+            ("quit", &order, "q", send(":quit")),
+            ("help", &order, "?", Action::ToggleHelp),
+            ("flag", &order, "f", send(":flag")),
+            ("skip", &true_false, "s", send(":skip")),
+            ("review", &selection, "r", send(":review")),
+            ("free text", &user_input, "Lucky⌫⌫y⏎", send("Lucy")),
+            ("free text quit", &user_input, "⎋", send(":quit")),
+            ("free text help", &user_input, "?", Action::Nothing),
+            ("flashcard reveal", &card, "⏎", send("")),
+            ("flashcard reveal with space", &card, "␣", send("")),
+            ("flashcard not revealed", &card, "3", Action::Nothing),
+            ("flashcard grade", &revealed, "3", send("3")),
+            ("flashcard no such grade", &revealed, "5⏎", Action::Nothing),
+            ("flashcard quit", &revealed, "q", send(":quit")),
+            ("save", &selection, "w", send(":save")),
+        ];
+
+        for (case_name, view, keys, expected) in cases {
+            let mut picker = Picker::default();
+            let mut action = Action::Nothing;
+            for key in keys.chars() {
+                let code = match key {
+                    '↑' => KeyCode::Up,
+                    '↓' => KeyCode::Down,
+                    '→' => KeyCode::Right,
+                    '␣' => KeyCode::Char(' '),
+                    '⏎' => KeyCode::Enter,
+                    '⌫' => KeyCode::Backspace,
+                    '⎋' => KeyCode::Esc,
+                    c => KeyCode::Char(c),
+                };
+                action = picker.key(view, KeyEvent::from(code));
+            }
+
+            assert_eq!(action, expected, "case: {case_name} failed");
+        }
+
+        // Free text takes every other key as typed.
+        let save = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(Picker::default().key(&user_input, save), send(":save"));
+    }
+
+    #[test]
+    fn test_review_keys() {
+        let cases = vec![
+            ("question number", "12⏎", send("12")),
+            ("command", ":submit⏎", send(":submit")),
+            ("backspace", "13⌫2⏎", send("12")),
+            ("back", "1⎋", send("")),
+            ("scroll", "↓↓↑", Action::Nothing),
+        ];
+
+        for (case_name, keys, expected) in cases {
+            let mut app = App::new();
+            app.review = Some(Review::default());
+            let mut action = Action::Nothing;
+            for key in keys.chars() {
+                let code = match key {
+                    '↑' => KeyCode::Up,
+                    '↓' => KeyCode::Down,
+                    '⏎' => KeyCode::Enter,
+                    '⌫' => KeyCode::Backspace,
+                    '⎋' => KeyCode::Esc,
+                    c => KeyCode::Char(c),
+                };
+                action = app.key(KeyEvent::from(code));
+            }
+
+            assert_eq!(action, expected, "case: {case_name} failed");
+        }
+
+        let mut app = App::new();
+        app.review = Some(Review::default());
+        let save = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        assert_eq!(app.key(save), send(":save"));
+
+        let mut review = Review::default();
+        review.key(KeyEvent::from(KeyCode::Down));
+        review.key(KeyEvent::from(KeyCode::Down));
+        review.key(KeyEvent::from(KeyCode::Up));
+        assert_eq!(review.scroll, 1);
+    }
+
+    /// The screen `app` draws on a 60 by 14 terminal.
+    fn draw(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let screen: Vec<String> = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(60)
+            .map(|x| x.iter().map(|x| x.symbol()).collect::<String>())
+            .collect();
+        screen.join("\n")
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut app = App::new();
+        app.view = Some(view(
+            QuestionType::Order,
+            Some(vec!["Amazon Lily Arc", "Impel Down Arc"]),
+            false,
+        ));
+        app.picker.picked = vec![1];
+        app.notice = Some("Invalid answer: Missing a".to_string());
+
+        let screen = draw(&app);
+
+        for expected in [
+            "Question 2/4",
+            "What devil fruit did Luffy eat?",
+            "Pick the items in the correct order",
+            "> [ ] a. Amazon Lily Arc",
+            "[1] b. Impel Down Arc",
+            "Invalid answer: Missing a",
+            "Question 2/4 | Answered 1/4 | 00:00 | ? help",
+        ] {
+            assert!(screen.contains(expected), "{expected} not in\n{screen}");
+        }
+    }
+
+    #[test]
+    fn test_draw_flashcard() {
+        let mut app = App::new();
+        app.view = Some(QuestionView {
+            flashcard: true,
+            ..view(QuestionType::UserInput, None, false)
+        });
+        let screen = draw(&app);
+        for expected in [
+            "Card 2/4",
+            "What devil fruit did Luffy eat?",
+            "Press Enter or Space to show the answer",
+            "Card 2/4 | Graded 1/4 | 00:00 | ? help",
+        ] {
+            assert!(screen.contains(expected), "{expected} not in\n{screen}");
+        }
+
+        app.view.as_mut().unwrap().back = Some("Gum Gum Fruit".to_string());
+        let screen = draw(&app);
+        for expected in ["Answer (1 again, 2 hard, 3 good, 4 easy)", "Gum Gum Fruit"] {
+            assert!(screen.contains(expected), "{expected} not in\n{screen}");
+        }
+    }
+}